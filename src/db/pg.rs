@@ -1,8 +1,41 @@
 use crate::{
-    models::message::{MessageStatus, MessageType},
+    models::{
+        credential::{CredentialKind, GitCredential},
+        message::{MessageStatus, MessageType},
+        release::Release,
+        user::User,
+    },
     shared::error::AppError,
 };
 
+// 远程仓库凭据，每个用户每个主机一条
+const CREATE_GIT_CREDENTIALS: &str = r#"
+    CREATE TABLE IF NOT EXISTS git_credentials (
+        id SERIAL PRIMARY KEY,
+        username TEXT NOT NULL,
+        host TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        auth_username TEXT,
+        secret TEXT NOT NULL,
+        passphrase TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        UNIQUE (username, host)
+    )
+"#;
+
+// 标签的发布说明，每个仓库每个标签一条
+const CREATE_REPO_RELEASES: &str = r#"
+    CREATE TABLE IF NOT EXISTS repo_releases (
+        id SERIAL PRIMARY KEY,
+        username TEXT NOT NULL,
+        repo_name TEXT NOT NULL,
+        tag_name TEXT NOT NULL,
+        notes TEXT NOT NULL,
+        updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+        UNIQUE (username, repo_name, tag_name)
+    )
+"#;
+
 #[derive(Clone)]
 pub struct PostgrePool {
     pub pool: sqlx::PgPool,
@@ -20,6 +53,28 @@ impl PostgrePool {
         self.pool.close().await;
     }
 
+    /// 创建凭据和发布说明表，已存在时不做改动
+    pub async fn create_tables(&self) -> Result<(), AppError> {
+        for statement in [CREATE_GIT_CREDENTIALS, CREATE_REPO_RELEASES] {
+            sqlx::query(statement)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to create table: {}", e))
+                })?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_user(&self, username: &str) -> Result<User, AppError> {
+        sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = $1")
+            .bind(username)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database query failed: {}", e)))?
+            .ok_or_else(|| AppError::NotFound(format!("User not found: {}", username)))
+    }
+
     pub async fn add_message_for_users(
         &self,
         usernames: &Vec<String>, // Changed from user_id_vec for clarity, assuming these are usernames
@@ -64,4 +119,148 @@ impl PostgrePool {
 
         Ok(())
     }
+
+    /// 保存用户在某个远端主机上的凭据，同一主机只保留一条
+    pub async fn save_credential(
+        &self,
+        username: &str,
+        host: &str,
+        kind: &CredentialKind,
+        auth_username: Option<&str>,
+        secret: &str,             // 已加密
+        passphrase: Option<&str>, // 已加密
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO git_credentials (username, host, kind, auth_username, secret, passphrase, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (username, host) DO UPDATE
+            SET kind = EXCLUDED.kind,
+                auth_username = EXCLUDED.auth_username,
+                secret = EXCLUDED.secret,
+                passphrase = EXCLUDED.passphrase,
+                created_at = EXCLUDED.created_at
+            "#,
+        )
+        .bind(username)
+        .bind(host)
+        .bind(kind.to_string())
+        .bind(auth_username)
+        .bind(secret)
+        .bind(passphrase)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to save credential: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub async fn list_credentials(&self, username: &str) -> Result<Vec<GitCredential>, AppError> {
+        sqlx::query_as::<_, GitCredential>(
+            "SELECT * FROM git_credentials WHERE username = $1 ORDER BY host",
+        )
+        .bind(username)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database query failed: {}", e)))
+    }
+
+    pub async fn get_credential(
+        &self,
+        username: &str,
+        host: &str,
+    ) -> Result<Option<GitCredential>, AppError> {
+        sqlx::query_as::<_, GitCredential>(
+            "SELECT * FROM git_credentials WHERE username = $1 AND host = $2",
+        )
+        .bind(username)
+        .bind(host)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database query failed: {}", e)))
+    }
+
+    pub async fn delete_credential(&self, username: &str, host: &str) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM git_credentials WHERE username = $1 AND host = $2")
+            .bind(username)
+            .bind(host)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to delete credential: {}", e))
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!(
+                "Credential not found: {}",
+                host
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 保存标签的发布说明，已存在时覆盖
+    pub async fn save_release(
+        &self,
+        username: &str,
+        repo_name: &str,
+        tag_name: &str,
+        notes: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            INSERT INTO repo_releases (username, repo_name, tag_name, notes, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (username, repo_name, tag_name) DO UPDATE
+            SET notes = EXCLUDED.notes,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(username)
+        .bind(repo_name)
+        .bind(tag_name)
+        .bind(notes)
+        .bind(chrono::Utc::now())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to save release: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub async fn list_releases(
+        &self,
+        username: &str,
+        repo_name: &str,
+    ) -> Result<Vec<Release>, AppError> {
+        sqlx::query_as::<_, Release>(
+            "SELECT * FROM repo_releases WHERE username = $1 AND repo_name = $2",
+        )
+        .bind(username)
+        .bind(repo_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database query failed: {}", e)))
+    }
+
+    pub async fn delete_release(
+        &self,
+        username: &str,
+        repo_name: &str,
+        tag_name: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            "DELETE FROM repo_releases WHERE username = $1 AND repo_name = $2 AND tag_name = $3",
+        )
+        .bind(username)
+        .bind(repo_name)
+        .bind(tag_name)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to delete release: {}", e)))?;
+
+        Ok(())
+    }
 }
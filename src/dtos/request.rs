@@ -1,4 +1,7 @@
-use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
 use validator::Validate;
 
 #[derive(Debug, Deserialize, Validate)]
@@ -35,6 +38,86 @@ pub struct RepoRequest {
     pub limit: Option<usize>,
 
     pub page: Option<usize>,
+
+    // 上一页返回的 next_cursor，指定后忽略 page 和 rev，翻页期间分支上的新提交不会出现
+    // 提交时间早于其父提交（时钟偏差）时，同一个提交可能在前后两页中重复出现
+    // 历史中并行的分支过多时无法生成游标，返回 400，此时改用 page
+    pub cursor: Option<String>,
+
+    // 起始分支、标签、提交 ID 或任意 revspec，默认 HEAD
+    pub rev: Option<String>,
+
+    // 作者名或邮箱，不区分大小写的子串匹配
+    pub author: Option<String>,
+
+    // 提交者名或邮箱，不区分大小写的子串匹配
+    pub committer: Option<String>,
+
+    // 提交说明，默认不区分大小写的子串匹配，regex 为 true 时按正则匹配
+    pub message: Option<String>,
+
+    pub regex: Option<bool>,
+
+    // 提交时间范围，支持 YYYY-MM-DD、RFC 3339 或 Unix 秒
+    pub since: Option<String>,
+
+    pub until: Option<String>,
+
+    // 只返回修改过该文件或目录的提交
+    pub path: Option<String>,
+
+    // 合并提交：show（默认）、hide、only
+    pub merges: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CommitGraphRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    // 逗号分隔的分支、标签或提交，默认 HEAD 和所有本地分支
+    pub refs: Option<String>,
+
+    // 上一页返回的 next_cursor，指定后忽略 page
+    pub cursor: Option<String>,
+
+    pub limit: Option<usize>,
+
+    pub page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct FileHistoryRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    // 文件或目录路径
+    #[validate(required(message = "File path is required"))]
+    pub path: Option<String>,
+
+    // 分支、标签、提交 ID 或任意 revspec，默认 HEAD
+    pub rev: Option<String>,
+
+    pub limit: Option<usize>,
+
+    pub page: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct BlameRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "File path is required"))]
+    pub path: Option<String>,
+
+    // 分支、标签、提交 ID 或任意 revspec，默认 HEAD
+    pub rev: Option<String>,
+
+    // 只返回指定行范围，行号从 1 开始，包含两端
+    pub start_line: Option<usize>,
+
+    pub end_line: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -43,8 +126,150 @@ pub struct CloneRepoRequest {
     pub repo_name: Option<String>,
 }
 
-// todo();
-pub struct CommitRepoRequest {}
+#[derive(Debug, Deserialize, Validate)]
+pub struct CommitRepoRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "message is required"))]
+    pub message: Option<String>,
+
+    // 要提交的文件路径，为空时提交所有更改
+    pub paths: Option<Vec<String>>,
+
+    // 可选的作者信息，默认使用当前用户
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct UpdateFileRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "file_path is required"))]
+    pub file_path: Option<String>,
+
+    // 文件的完整新内容
+    #[validate(required(message = "content is required"))]
+    pub content: Option<String>,
+
+    // 提交说明，默认 "Update <文件路径>"
+    pub message: Option<String>,
+
+    // 提交到的分支，默认当前分支
+    pub branch_name: Option<String>,
+
+    // 开始编辑时文件的 blob ID
+    pub base_blob_id: Option<String>,
+
+    // 开始编辑时的提交，新建文件时使用
+    pub base_commit_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CommitFilesRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "message is required"))]
+    pub message: Option<String>,
+
+    // 提交到的分支，默认当前分支
+    pub branch_name: Option<String>,
+
+    // 涉及的文件在此提交之后被修改过时返回 409
+    pub base_commit_id: Option<String>,
+
+    // 提交到当前分支时是否同步更新索引和工作区，默认不更新
+    pub update_workdir: Option<bool>,
+
+    // 按顺序执行的文件操作
+    #[validate(required(message = "actions is required"))]
+    pub actions: Option<Vec<FileActionRequest>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileActionRequest {
+    // create、update、delete、move、chmod
+    pub action: Option<String>,
+    pub file_path: Option<String>,
+    // move 的原路径
+    pub previous_path: Option<String>,
+    pub content: Option<String>,
+    // chmod 或 create 时设置可执行位
+    pub executable: Option<bool>,
+}
+
+// 文件内容通过 multipart 请求体上传，其余参数放在查询字符串中
+#[derive(Debug, Deserialize, Validate)]
+pub struct UploadFilesRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 上传到的目录，默认仓库根目录
+    pub directory: Option<String>,
+
+    // 提交说明，默认根据上传的文件生成
+    pub message: Option<String>,
+
+    // 提交到的分支，默认当前分支
+    pub branch_name: Option<String>,
+
+    // 上传的文件在此提交之后被修改过时返回 409
+    pub base_commit_id: Option<String>,
+
+    // 是否覆盖已存在的文件，默认不覆盖
+    pub overwrite: Option<bool>,
+
+    // 提交到当前分支时是否同步更新索引和工作区，默认不更新
+    pub update_workdir: Option<bool>,
+}
+
+// 差异相关接口共用的选项，通过 flatten 放在各自的查询参数中
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DiffQuery {
+    // 空白处理：none、ignore_all、ignore_change、ignore_eol
+    pub whitespace: Option<String>,
+
+    // 上下文行数，默认 3
+    #[serde(default, deserialize_with = "from_query")]
+    pub context_lines: Option<u32>,
+
+    // 是否检测重命名，默认开启：重命名的文件作为一条 renamed 记录返回，
+    // 而不是一条删除加一条新增，传 false 恢复为后者
+    #[serde(default, deserialize_with = "from_query")]
+    pub renames: Option<bool>,
+
+    // 是否检测复制，默认关闭
+    #[serde(default, deserialize_with = "from_query")]
+    pub copies: Option<bool>,
+
+    // 重命名、复制的相似度阈值 0-100
+    #[serde(default, deserialize_with = "from_query")]
+    pub similarity: Option<u16>,
+
+    // 只比较这些路径，多个用逗号分隔，支持通配符
+    pub pathspec: Option<String>,
+}
+
+// flatten 之后查询参数都按字符串传入，数字和布尔值需要自行解析
+fn from_query<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct GetReopDiffRequest {
@@ -53,6 +278,141 @@ pub struct GetReopDiffRequest {
 
     #[validate(required(message = "Commit ID is required"))]
     pub commit_id: Option<String>,
+
+    // 差异选项
+    #[serde(flatten)]
+    pub diff: DiffQuery,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetFileDiffRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "Commit ID is required"))]
+    pub commit_id: Option<String>,
+
+    // 文件路径，重命名的文件可以使用新路径或旧路径
+    #[validate(required(message = "File path is required"))]
+    pub path: Option<String>,
+
+    // 差异选项
+    #[serde(flatten)]
+    pub diff: DiffQuery,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RepoStatusRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    // 是否列出被忽略的文件，默认不列出
+    pub ignored: Option<bool>,
+
+    // 差异选项
+    #[serde(flatten)]
+    pub diff: DiffQuery,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct StatusFileDiffRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "File path is required"))]
+    pub path: Option<String>,
+
+    // true 查看已暂存的修改（索引相对 HEAD），默认查看未暂存的修改（工作区相对索引）
+    pub staged: Option<bool>,
+
+    // 差异选项
+    #[serde(flatten)]
+    pub diff: DiffQuery,
+}
+
+// 暂存、取消暂存和丢弃修改共用
+#[derive(Debug, Deserialize, Validate)]
+pub struct StatusPathsRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 文件或目录路径
+    #[validate(required(message = "paths is required"))]
+    pub paths: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct StashListRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct StashSaveRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 储藏说明，默认使用 HEAD 的提交说明
+    pub message: Option<String>,
+
+    // 是否同时储藏未跟踪的文件，默认否
+    pub include_untracked: Option<bool>,
+
+    // 是否保留已暂存的修改，默认否
+    pub keep_index: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct StashShowRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // stash@{index}，与 stash_id 二选一
+    pub index: Option<usize>,
+
+    // 储藏提交的 ID，优先于 index
+    pub stash_id: Option<String>,
+
+    // 差异选项
+    #[serde(flatten)]
+    pub diff: DiffQuery,
+}
+
+// apply、pop 和 drop 共用
+#[derive(Debug, Deserialize, Validate)]
+pub struct StashApplyRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // stash@{index}，与 stash_id 二选一
+    pub index: Option<usize>,
+
+    // 储藏提交的 ID，优先于 index
+    pub stash_id: Option<String>,
+
+    // 是否同时恢复暂存状态，只用于 apply 和 pop，默认否
+    pub restore_index: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CompareRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    // 基准版本，例如 main
+    #[validate(required(message = "Base is required"))]
+    pub base: Option<String>,
+
+    // 对比版本，例如 feature 分支
+    #[validate(required(message = "Head is required"))]
+    pub head: Option<String>,
+
+    // two_dot 或 three_dot，默认 three_dot
+    pub mode: Option<String>,
+
+    // 差异选项
+    #[serde(flatten)]
+    pub diff: DiffQuery,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -63,7 +423,19 @@ pub struct GetRepoFilesRequest {
     // 要浏览的目录路径，默认为根目录
     pub path: Option<String>,
 
-    // 分支名称，默认使用当前分支
+    // 是否递归返回整棵子树，默认只返回一层
+    pub recursive: Option<bool>,
+
+    // 分页游标，即上一页返回的 next_cursor
+    pub cursor: Option<String>,
+
+    // 每页条目数，仅非递归模式有效
+    pub limit: Option<usize>,
+
+    // 版本：分支、标签、提交 ID 或任意 revspec（如 HEAD~3），默认使用 HEAD
+    pub rev: Option<String>,
+
+    // 兼容旧参数，等同于 rev
     pub branch: Option<String>,
 }
 
@@ -75,10 +447,28 @@ pub struct GetFileContentRequest {
     #[validate(required(message = "File path is required"))]
     pub file_path: Option<String>,
 
-    // 可选的分支名称
+    // 版本：分支、标签、提交 ID 或任意 revspec（如 HEAD~3），默认使用 HEAD
+    pub rev: Option<String>,
+
+    // 兼容旧参数，等同于 rev
     pub branch: Option<String>,
 }
 
+#[derive(Debug, Deserialize, Validate)]
+pub struct ArchiveRepoRequest {
+    #[validate(required(message = "Repository name is required"))]
+    pub repo_name: Option<String>,
+
+    // 版本：分支、标签或提交 ID，默认使用 HEAD
+    pub rev: Option<String>,
+
+    // 只打包该目录，默认为整个仓库
+    pub path: Option<String>,
+
+    // zip 或 tar.gz，默认 zip
+    pub format: Option<String>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct UserUpdateRequest {
     #[validate(email(message = "Invalid email format"))]
@@ -119,8 +509,98 @@ pub struct GetRepoBranchesRequest {
     #[validate(required(message = "repo_name is required"))]
     pub repo_name: Option<String>,
 
+    // 可选，只返回名称包含该字符串的分支
+    pub branch_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateBranchRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "branch_name is required"))]
+    pub branch_name: Option<String>,
+
+    // 新分支的起点，可以是提交 ID、分支或标签，默认为 HEAD
+    pub start_point: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DelBranchRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
     #[validate(required(message = "branch_name is required"))]
     pub branch_name: Option<String>,
+
+    // 强制删除未合并的分支
+    pub force: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RenameBranchRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "branch_name is required"))]
+    pub branch_name: Option<String>,
+
+    #[validate(required(message = "new_branch_name is required"))]
+    pub new_branch_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CheckoutBranchRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "branch_name is required"))]
+    pub branch_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct GetRepoTagsRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateTagRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "tag_name is required"))]
+    pub tag_name: Option<String>,
+
+    // 标签指向的提交、分支或标签，默认为 HEAD
+    pub target: Option<String>,
+
+    // 有 message 时创建附注标签，否则创建轻量标签
+    pub message: Option<String>,
+
+    // 可选的发布说明
+    pub release_notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DelTagRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "tag_name is required"))]
+    pub tag_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReleaseNotesRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    #[validate(required(message = "tag_name is required"))]
+    pub tag_name: Option<String>,
+
+    #[validate(required(message = "notes is required"))]
+    pub notes: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -130,6 +610,96 @@ pub struct PullRepoRequest {
 
     #[validate(required(message = "branch_name is required"))]
     pub branch_name: Option<String>,
+
+    // 分支分叉时的处理方式："merge"（默认）或 "rebase"
+    pub strategy: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RevertCommitRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 要撤销的提交
+    #[validate(required(message = "commit_id is required"))]
+    pub commit_id: Option<String>,
+
+    // 生成新提交的分支，默认当前分支
+    pub branch_name: Option<String>,
+
+    // 撤销合并提交时保留的父提交，从 1 开始
+    pub mainline: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CherryPickRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 要应用的提交
+    #[validate(required(message = "commit_id is required"))]
+    pub commit_id: Option<String>,
+
+    // 应用到的分支，默认当前分支
+    pub branch_name: Option<String>,
+
+    // 应用合并提交时作为基准的父提交，从 1 开始
+    pub mainline: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetBranchRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 重置到的提交，可以是任意 revspec，包括 hard 重置返回的 backup_ref
+    #[validate(required(message = "commit_id is required"))]
+    pub commit_id: Option<String>,
+
+    // 要重置的分支，默认当前分支
+    pub branch_name: Option<String>,
+
+    // "soft"、"mixed"（默认）或 "hard"
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PushRepoRequest {
+    #[validate(required(message = "repo_name is required"))]
+    pub repo_name: Option<String>,
+
+    // 远端名称，默认为 origin
+    pub remote_name: Option<String>,
+
+    // 要推送的本地分支，默认为当前分支
+    pub branch_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SaveCredentialRequest {
+    // 远端主机名，例如 github.com
+    #[validate(required(message = "host is required"))]
+    pub host: Option<String>,
+
+    // "https_token" 或 "ssh_key"
+    #[validate(required(message = "kind is required"))]
+    pub kind: Option<String>,
+
+    // 认证用户名，https 默认为 x-access-token，ssh 默认为 git
+    pub username: Option<String>,
+
+    // https token 或 ssh 私钥内容
+    #[validate(required(message = "secret is required"))]
+    pub secret: Option<String>,
+
+    // ssh 私钥的密码（可选）
+    pub passphrase: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct DelCredentialRequest {
+    #[validate(required(message = "host is required"))]
+    pub host: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Clone, Validate)]
@@ -142,4 +712,7 @@ pub struct AddMessageRequest {
 #[derive(Deserialize, Debug, Clone)]
 pub struct RepoTotalCountRequest {
     pub repo_name: Option<String>,
+
+    // 分支、标签、提交 ID 或任意 revspec，默认 HEAD
+    pub rev: Option<String>,
 }
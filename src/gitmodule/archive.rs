@@ -0,0 +1,267 @@
+use std::io::{self, BufWriter, Read, Write};
+
+use chrono::{Datelike, Timelike};
+use flate2::{Compression, write::GzEncoder};
+use futures::{SinkExt, channel::mpsc};
+use git2::{FileMode, Odb, Oid, Repository};
+use tracing::{error, info};
+use zip::write::{SimpleFileOptions, StreamWriter};
+use zip::{CompressionMethod, ZipWriter};
+
+use super::GitManager;
+use super::blob::blob_reader;
+use super::structs::{ArchiveFormat, ArchiveInfo, GitFileEntry};
+use super::tree::normalize_tree_path;
+use crate::shared::error::AppError;
+
+// 每次发送给客户端的数据块大小
+const ARCHIVE_CHUNK_SIZE: usize = 64 * 1024;
+// 通道中最多缓存的数据块数，客户端读取慢时打包线程会等待
+const ARCHIVE_CHANNEL_SIZE: usize = 8;
+
+pub type ArchiveStream = mpsc::Receiver<Result<Vec<u8>, io::Error>>;
+
+// 把写入的数据转发到通道，接收端断开时返回错误以终止打包
+struct ChannelWriter {
+    tx: mpsc::Sender<Result<Vec<u8>, io::Error>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        futures::executor::block_on(self.tx.send(Ok(buf.to_vec())))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "archive receiver closed"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+type Sink = BufWriter<ChannelWriter>;
+
+enum ArchiveWriter {
+    Zip(ZipWriter<StreamWriter<Sink>>),
+    TarGz(tar::Builder<GzEncoder<Sink>>),
+}
+
+impl ArchiveWriter {
+    fn new(format: ArchiveFormat, sink: Sink) -> Self {
+        match format {
+            ArchiveFormat::Zip => ArchiveWriter::Zip(ZipWriter::new_stream(sink)),
+            ArchiveFormat::TarGz => ArchiveWriter::TarGz(tar::Builder::new(GzEncoder::new(
+                sink,
+                Compression::default(),
+            ))),
+        }
+    }
+
+    fn add_dir(&mut self, path: &str, mtime: i64) -> io::Result<()> {
+        match self {
+            // 流式写入的 zip 目录条目会被 unzip 判定为异常，解压时目录会根据文件路径自动创建
+            ArchiveWriter::Zip(_) => Ok(()),
+            ArchiveWriter::TarGz(tar) => {
+                let mut header = tar_header(tar::EntryType::Directory, 0o755, mtime, 0);
+                tar.append_data(&mut header, path, io::empty())
+            }
+        }
+    }
+
+    fn add_file(
+        &mut self,
+        path: &str,
+        mode: u32,
+        mtime: i64,
+        size: u64,
+        data: &mut dyn io::Read,
+    ) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Zip(zip) => {
+                zip.start_file(path, zip_options(mode, mtime, size))
+                    .map_err(io::Error::other)?;
+                io::copy(data, zip).map(|_| ())
+            }
+            ArchiveWriter::TarGz(tar) => {
+                let mut header = tar_header(tar::EntryType::Regular, mode, mtime, size);
+                tar.append_data(&mut header, path, data)
+            }
+        }
+    }
+
+    fn add_symlink(&mut self, path: &str, target: &str, mtime: i64) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Zip(zip) => zip
+                .add_symlink(path, target, zip_options(0o777, mtime, 0))
+                .map_err(io::Error::other),
+            ArchiveWriter::TarGz(tar) => {
+                let mut header = tar_header(tar::EntryType::Symlink, 0o777, mtime, 0);
+                tar.append_link(&mut header, path, target)
+            }
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        let mut sink = match self {
+            ArchiveWriter::Zip(zip) => zip.finish().map_err(io::Error::other)?.into_inner(),
+            ArchiveWriter::TarGz(tar) => tar.into_inner()?.finish()?,
+        };
+        sink.flush()
+    }
+}
+
+fn zip_options(mode: u32, mtime: i64, size: u64) -> SimpleFileOptions {
+    let mut options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(mode)
+        .large_file(size >= u32::MAX as u64);
+    // zip 只能表示 1980 年之后的时间
+    if let Some(time) = chrono::DateTime::from_timestamp(mtime, 0).and_then(|t| {
+        zip::DateTime::from_date_and_time(
+            t.year().try_into().ok()?,
+            t.month() as u8,
+            t.day() as u8,
+            t.hour() as u8,
+            t.minute() as u8,
+            t.second() as u8,
+        )
+        .ok()
+    }) {
+        options = options.last_modified_time(time);
+    }
+    options
+}
+
+fn tar_header(kind: tar::EntryType, mode: u32, mtime: i64, size: u64) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(kind);
+    header.set_mode(mode);
+    header.set_mtime(mtime.max(0) as u64);
+    header.set_size(size);
+    header
+}
+
+// 清理版本名中不适合作为文件名的字符
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+impl GitManager {
+    // 校验版本和目录后在后台线程中打包，数据通过通道按块返回
+    pub fn archive_repository(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        rev: Option<&str>,
+        directory_path: Option<&str>,
+        format: ArchiveFormat,
+    ) -> Result<(ArchiveInfo, ArchiveStream), AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit_or_head(&repo, rev)?;
+        let root = commit
+            .tree()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+        let directory_path = normalize_tree_path(directory_path.unwrap_or(""));
+        let tree = self.find_subtree(&repo, &root, &directory_path)?;
+
+        // 压缩包名称和顶层目录，例如 repo-v1.0 或 repo-1a2b3c4-docs
+        let version = match rev {
+            Some(rev) => sanitize_name(rev.trim()),
+            None => commit.id().to_string()[..7].to_string(),
+        };
+        let mut prefix = format!("{}-{}", sanitize_name(repo_name), version);
+        if let Some(dir) = directory_path.rsplit('/').next().filter(|d| !d.is_empty()) {
+            prefix = format!("{}-{}", prefix, sanitize_name(dir));
+        }
+
+        let info = ArchiveInfo {
+            file_name: format!("{}.{}", prefix, format.extension()),
+            commit_id: commit.id().to_string(),
+        };
+        let mtime = commit.time().seconds();
+
+        // 先列出所有条目，路径直接以顶层目录开头，出错时还能返回错误响应
+        let odb = repo
+            .odb()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to open odb: {}", e)))?;
+        let entries = self.get_tree_entries(&repo, &odb, &tree, &prefix)?;
+
+        let (tx, rx) = mpsc::channel(ARCHIVE_CHANNEL_SIZE);
+        let mut error_tx = tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = Repository::open(&repo_path).and_then(|repo| {
+                let odb = repo.odb()?;
+                let sink = BufWriter::with_capacity(ARCHIVE_CHUNK_SIZE, ChannelWriter { tx });
+                let mut writer = ArchiveWriter::new(format, sink);
+                writer
+                    .add_dir(&format!("{}/", prefix), mtime)
+                    .and_then(|_| append_entries(&odb, &mut writer, &entries, mtime))
+                    .and_then(|_| writer.finish())
+                    .map_err(|e| git2::Error::from_str(&e.to_string()))
+            });
+
+            match result {
+                Ok(()) => info!("Archive {} finished", prefix),
+                Err(e) => {
+                    error!("Archive {} failed: {}", prefix, e);
+                    // 通知客户端响应体异常结束，避免收到不完整的文件
+                    let _ = futures::executor::block_on(
+                        error_tx.send(Err(io::Error::other(e.message().to_string()))),
+                    );
+                }
+            }
+        });
+
+        Ok((info, rx))
+    }
+}
+
+// 按列出的目录结构写入条目，文件内容从对象库流式读取
+fn append_entries(
+    odb: &Odb,
+    writer: &mut ArchiveWriter,
+    entries: &[GitFileEntry],
+    mtime: i64,
+) -> io::Result<()> {
+    for entry in entries {
+        let oid =
+            Oid::from_str(&entry.id).map_err(|e| io::Error::other(e.message().to_string()))?;
+        match entry.filemode {
+            mode if mode == i32::from(FileMode::Tree) => {
+                writer.add_dir(&format!("{}/", entry.path), mtime)?;
+                append_entries(odb, writer, &entry.children, mtime)?;
+            }
+            mode if mode == i32::from(FileMode::Link) => {
+                let mut target = Vec::new();
+                blob_reader(odb, oid)?.read_to_end(&mut target)?;
+                writer.add_symlink(&entry.path, &String::from_utf8_lossy(&target), mtime)?;
+            }
+            mode if mode == i32::from(FileMode::Blob)
+                || mode == i32::from(FileMode::BlobExecutable)
+                || mode == i32::from(FileMode::BlobGroupWritable) =>
+            {
+                let size = entry
+                    .size
+                    .ok_or_else(|| io::Error::other(format!("Blob not found: {}", entry.path)))?;
+                let mode = if mode == i32::from(FileMode::BlobExecutable) {
+                    0o755
+                } else {
+                    0o644
+                };
+                writer.add_file(&entry.path, mode, mtime, size, &mut blob_reader(odb, oid)?)?;
+            }
+            // 子模块不包含在压缩包中
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use git2::BlameOptions;
+
+use super::GitManager;
+use super::structs::{BlameHunk, BlameResult};
+use crate::shared::error::AppError;
+
+impl GitManager {
+    // 解析版本对应的提交和文件 blob，作为 blame 缓存的键
+    pub fn get_blame_target(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        file_path: &str,
+        rev: Option<&str>,
+    ) -> Result<(String, String), AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit_or_head(&repo, rev)?;
+        let blob_id = self.find_blob_id(&repo, file_path, Some(&commit.id().to_string()))?;
+
+        Ok((commit.id().to_string(), blob_id.to_string()))
+    }
+
+    // 计算文件在指定提交中每一行最后修改的提交，耗时与文件历史长度相关
+    pub fn blame_file(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        file_path: &str,
+        commit_id: &str,
+    ) -> Result<BlameResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit(&repo, commit_id)?;
+        let file_path = file_path.trim_matches('/');
+        let blob_id = self.find_blob_id(&repo, file_path, Some(commit_id))?;
+
+        let mut opts = BlameOptions::new();
+        opts.newest_commit(commit.id());
+        let blame = repo
+            .blame_file(Path::new(file_path), Some(&mut opts))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to blame file: {}", e)))?;
+
+        let mut hunks = Vec::with_capacity(blame.len());
+        for hunk in blame.iter() {
+            let commit_id = hunk.final_commit_id();
+            let signature = hunk.final_signature();
+
+            hunks.push(BlameHunk {
+                start_line: hunk.final_start_line(),
+                lines: hunk.lines_in_hunk(),
+                commit_id: commit_id.to_string(),
+                author: format!(
+                    "{} <{}>",
+                    signature.name().unwrap_or(""),
+                    signature.email().unwrap_or("")
+                ),
+                time: signature.when().seconds(),
+                orig_path: hunk
+                    .path()
+                    .map(|p| p.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| file_path.to_string()),
+                orig_start_line: hunk.orig_start_line(),
+                boundary: hunk.is_boundary(),
+            });
+        }
+
+        Ok(BlameResult {
+            path: file_path.to_string(),
+            commit_id: commit.id().to_string(),
+            blob_id: blob_id.to_string(),
+            total_lines: hunks.iter().map(|h| h.lines).sum(),
+            hunks,
+        })
+    }
+}
+
+// 只保留与 [start_line, end_line] 相交的部分，首尾 hunk 按范围裁剪
+pub fn select_blame_lines(
+    mut blame: BlameResult,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+) -> Result<BlameResult, AppError> {
+    if start_line.is_none() && end_line.is_none() {
+        return Ok(blame);
+    }
+
+    let start = start_line.unwrap_or(1);
+    let end = end_line.unwrap_or(blame.total_lines);
+    if start == 0 || end < start || start > blame.total_lines {
+        return Err(AppError::BadRequest(format!(
+            "Invalid line range {}-{}, file has {} lines",
+            start, end, blame.total_lines
+        )));
+    }
+
+    blame.hunks = blame
+        .hunks
+        .into_iter()
+        .filter_map(|mut hunk| {
+            let hunk_end = hunk.start_line + hunk.lines - 1;
+            if hunk_end < start || hunk.start_line > end {
+                return None;
+            }
+            let skip = start.saturating_sub(hunk.start_line);
+            hunk.start_line += skip;
+            hunk.orig_start_line += skip;
+            hunk.lines = hunk_end.min(end) - hunk.start_line + 1;
+            Some(hunk)
+        })
+        .collect();
+
+    Ok(blame)
+}
@@ -0,0 +1,221 @@
+use std::io::{self, Read};
+use std::path::Path;
+
+use futures::SinkExt;
+use futures::channel::{mpsc, oneshot};
+use git2::{Blob, ErrorCode, ObjectType, Odb, OdbObject, Oid, Repository};
+use tracing::error;
+
+use super::GitManager;
+use super::structs::{BlobMeta, FileContent};
+use crate::shared::error::AppError;
+
+// 每次发送给客户端的数据块大小
+const BLOB_CHUNK_SIZE: usize = 64 * 1024;
+// 通道中最多缓存的数据块数，客户端读取慢时读取线程会等待
+const BLOB_CHANNEL_SIZE: usize = 8;
+// 识别内容类型时读取的文件头长度
+const BLOB_HEAD_SIZE: u64 = 8000;
+
+pub type BlobStream = mpsc::Receiver<Result<Vec<u8>, io::Error>>;
+
+impl GitManager {
+    // 在指定版本中查找文件对应的 blob
+    pub(super) fn find_blob_id(
+        &self,
+        repo: &Repository,
+        file_path: &str,
+        rev: Option<&str>,
+    ) -> Result<Oid, AppError> {
+        let commit = self.resolve_commit_or_head(repo, rev)?;
+        let tree = commit
+            .tree()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+
+        let entry = tree
+            .get_path(Path::new(file_path.trim_matches('/')))
+            .map_err(|_| AppError::NotFound(format!("File not found: {}", file_path)))?;
+        if entry.kind() != Some(ObjectType::Blob) {
+            return Err(AppError::BadRequest(format!(
+                "Path is not a file: {}",
+                file_path
+            )));
+        }
+
+        Ok(entry.id())
+    }
+
+    // 只读取对象头获取 blob 的 ID 和大小，用于 ETag 校验
+    pub fn get_blob_meta(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        file_path: &str,
+        rev: Option<&str>,
+    ) -> Result<BlobMeta, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let oid = self.find_blob_id(&repo, file_path, rev)?;
+        let (size, _) = repo
+            .odb()
+            .and_then(|odb| odb.read_header(oid))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to read blob: {}", e)))?;
+
+        Ok(BlobMeta {
+            id: oid.to_string(),
+            size: size as u64,
+        })
+    }
+
+    // 校验 blob 后在后台线程中按块读取闭区间 range 内的内容，未指定时读取全部
+    // 同时通过 head 返回文件开头，用于识别内容类型
+    pub fn stream_blob(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        blob_id: &str,
+        range: Option<(u64, u64)>,
+    ) -> Result<(oneshot::Receiver<Vec<u8>>, BlobStream), AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let oid = Oid::from_str(blob_id)
+            .map_err(|_| AppError::BadRequest(format!("Invalid blob id: {}", blob_id)))?;
+        let size = match repo.odb().and_then(|odb| odb.read_header(oid)) {
+            Ok((size, ObjectType::Blob)) => size as u64,
+            _ => return Err(AppError::NotFound(format!("Blob not found: {}", blob_id))),
+        };
+        let (start, len) = match range {
+            Some((start, end)) => (start, end + 1 - start),
+            None => (0, size),
+        };
+
+        let (head_tx, head_rx) = oneshot::channel();
+        let (tx, rx) = mpsc::channel(BLOB_CHANNEL_SIZE);
+        let mut error_tx = tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let result = Repository::open(&repo_path)
+                .map_err(|e| io::Error::other(e.message().to_string()))
+                .and_then(|repo| send_blob(&repo, oid, start, len, head_tx, tx));
+            if let Err(e) = result {
+                error!("Reading blob {} failed: {}", oid, e);
+                // 通知客户端响应体异常结束，避免收到不完整的文件
+                let _ = futures::executor::block_on(error_tx.send(Err(e)));
+            }
+        });
+
+        Ok((head_rx, rx))
+    }
+
+    pub fn get_file_content(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        file_path: &str,
+        rev: Option<&str>, // 分支、标签、提交 ID 或任意 revspec，默认 HEAD
+    ) -> Result<FileContent, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let oid = self.find_blob_id(&repo, file_path, rev)?;
+        let blob = repo
+            .find_blob(oid)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get blob: {}", e)))?;
+
+        Ok(file_content(&blob))
+    }
+}
+
+// 含 NUL 字节或不是合法 UTF-8 的文件按二进制处理，不返回内容
+pub(super) fn file_content(blob: &Blob) -> FileContent {
+    let content = if blob.is_binary() {
+        None
+    } else {
+        std::str::from_utf8(blob.content())
+            .ok()
+            .map(|s| s.to_string())
+    };
+
+    FileContent {
+        id: blob.id().to_string(),
+        binary: content.is_none(),
+        size: blob.size() as u64,
+        content,
+    }
+}
+
+// 打包对象整体解压后按顺序读取
+struct ObjectReader<'o> {
+    object: OdbObject<'o>,
+    position: usize,
+}
+
+impl Read for ObjectReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = &self.object.data()[self.position..];
+        let n = data.len().min(buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+// 按顺序读取 blob 的内容：松散对象边解压边读取；
+// libgit2 不支持流式读取打包对象，只能整体解压
+pub(super) fn blob_reader<'o>(odb: &'o Odb, oid: Oid) -> io::Result<Box<dyn Read + 'o>> {
+    let to_io = |e: git2::Error| io::Error::other(e.message().to_string());
+    match odb.reader(oid) {
+        Ok((reader, _, _)) => Ok(Box::new(reader)),
+        Err(e) if e.code() == ErrorCode::NotFound => {
+            let object = odb.read(oid).map_err(to_io)?;
+            Ok(Box::new(ObjectReader {
+                object,
+                position: 0,
+            }))
+        }
+        Err(e) => Err(to_io(e)),
+    }
+}
+
+// 先读出文件头交给 head，再跳过 start 之前的内容，把之后 len 个字节按块发送
+fn send_blob(
+    repo: &Repository,
+    oid: Oid,
+    start: u64,
+    len: u64,
+    head: oneshot::Sender<Vec<u8>>,
+    mut tx: mpsc::Sender<Result<Vec<u8>, io::Error>>,
+) -> io::Result<()> {
+    let to_io = |e: git2::Error| io::Error::other(e.message().to_string());
+
+    let odb = repo.odb().map_err(to_io)?;
+    let mut source = blob_reader(&odb, oid)?;
+
+    let mut head_bytes = Vec::new();
+    (&mut source)
+        .take(BLOB_HEAD_SIZE)
+        .read_to_end(&mut head_bytes)?;
+    let _ = head.send(head_bytes.clone());
+
+    let mut source = io::Cursor::new(head_bytes).chain(source);
+    io::copy(&mut (&mut source).take(start), &mut io::sink())?;
+    let mut remaining = len;
+    let mut buf = vec![0; BLOB_CHUNK_SIZE];
+    while remaining > 0 {
+        let size = remaining.min(BLOB_CHUNK_SIZE as u64) as usize;
+        let n = source.read(&mut buf[..size])?;
+        if n == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "blob is shorter than its header",
+            ));
+        }
+        remaining -= n as u64;
+        // 客户端断开后停止读取
+        if futures::executor::block_on(tx.send(Ok(buf[..n].to_vec()))).is_err() {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
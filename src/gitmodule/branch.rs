@@ -0,0 +1,241 @@
+use git2::{BranchType, Repository};
+use tracing::info;
+
+use super::GitManager;
+use super::structs::{BranchInfo, BranchKind};
+use crate::shared::error::AppError;
+
+impl GitManager {
+    pub fn list_branches(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        filter: Option<&str>,
+    ) -> Result<Vec<BranchInfo>, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let mut branches = Vec::new();
+        for branch in repo
+            .branches(None)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get branches: {}", e)))?
+        {
+            let (branch, branch_type) = branch.map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get branch: {}", e))
+            })?;
+            let Some(name) = branch.name().ok().flatten() else {
+                continue;
+            };
+            // 跳过 origin/HEAD 这类符号引用
+            if branch_type == BranchType::Remote && name.ends_with("/HEAD") {
+                continue;
+            }
+            if filter.is_some_and(|f| !name.contains(f)) {
+                continue;
+            }
+
+            branches.push(self.branch_info(&repo, &branch, branch_type)?);
+        }
+
+        Ok(branches)
+    }
+
+    fn branch_info(
+        &self,
+        repo: &Repository,
+        branch: &git2::Branch,
+        branch_type: BranchType,
+    ) -> Result<BranchInfo, AppError> {
+        let name = branch.name().ok().flatten().unwrap_or_default().to_string();
+        let tip = branch.get().peel_to_commit().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to get branch tip: {}", e))
+        })?;
+
+        let (kind, upstream, ahead, behind) = match branch_type {
+            BranchType::Local => match branch.upstream() {
+                Ok(upstream) => {
+                    let upstream_name = upstream.name().ok().flatten().map(|n| n.to_string());
+                    let (ahead, behind) = match upstream.get().target() {
+                        Some(upstream_oid) => repo
+                            .graph_ahead_behind(tip.id(), upstream_oid)
+                            .map(|(a, b)| (Some(a), Some(b)))
+                            .unwrap_or((None, None)),
+                        None => (None, None),
+                    };
+                    (BranchKind::Local, upstream_name, ahead, behind)
+                }
+                Err(_) => (BranchKind::Local, None, None, None),
+            },
+            BranchType::Remote => (BranchKind::Remote, None, None, None),
+        };
+
+        Ok(BranchInfo {
+            name,
+            kind,
+            is_head: branch.is_head(),
+            upstream,
+            ahead,
+            behind,
+            tip: Self::to_commit_info(&tip),
+        })
+    }
+
+    // 从任意提交或引用创建本地分支
+    pub fn create_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+        start_point: Option<&str>,
+    ) -> Result<BranchInfo, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit(&repo, start_point.unwrap_or("HEAD"))?;
+
+        if repo.find_branch(branch_name, BranchType::Local).is_ok() {
+            return Err(AppError::BadRequest(format!(
+                "Branch already exists: {}",
+                branch_name
+            )));
+        }
+
+        let branch = repo
+            .branch(branch_name, &commit, false)
+            .map_err(|e| AppError::BadRequest(format!("Failed to create branch: {}", e)))?;
+
+        info!("Created branch {} at {}", branch_name, commit.id());
+        self.branch_info(&repo, &branch, BranchType::Local)
+    }
+
+    pub fn delete_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+        force: bool,
+    ) -> Result<(), AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let mut branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| AppError::NotFound(format!("Branch not found: {}", branch_name)))?;
+
+        if branch.is_head() {
+            return Err(AppError::BadRequest(format!(
+                "Cannot delete the checked out branch: {}",
+                branch_name
+            )));
+        }
+
+        if !force {
+            // 与 git branch -d 一致：分支需已合并到上游或 HEAD
+            let tip = branch.get().target().ok_or_else(|| {
+                AppError::InternalServerError("Failed to get branch tip".to_string())
+            })?;
+            let merge_target = branch
+                .upstream()
+                .ok()
+                .and_then(|upstream| upstream.get().target())
+                .or_else(|| repo.head().ok().and_then(|head| head.target()));
+
+            let merged = merge_target.is_some_and(|target| {
+                target == tip || repo.graph_descendant_of(target, tip).unwrap_or(false)
+            });
+            if !merged {
+                return Err(AppError::BadRequest(format!(
+                    "Branch {} is not fully merged, use force to delete it",
+                    branch_name
+                )));
+            }
+        }
+
+        branch.delete().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to delete branch: {}", e))
+        })?;
+
+        info!("Deleted branch {}", branch_name);
+        Ok(())
+    }
+
+    pub fn rename_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+        new_name: &str,
+    ) -> Result<BranchInfo, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let mut branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| AppError::NotFound(format!("Branch not found: {}", branch_name)))?;
+
+        if repo.find_branch(new_name, BranchType::Local).is_ok() {
+            return Err(AppError::BadRequest(format!(
+                "Branch already exists: {}",
+                new_name
+            )));
+        }
+
+        let renamed = branch
+            .rename(new_name, false)
+            .map_err(|e| AppError::BadRequest(format!("Failed to rename branch: {}", e)))?;
+
+        self.branch_info(&repo, &renamed, BranchType::Local)
+    }
+
+    // 切换工作区到指定分支，只有远程分支时自动创建跟踪分支
+    pub fn checkout_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+    ) -> Result<BranchInfo, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let branch = match repo.find_branch(branch_name, BranchType::Local) {
+            Ok(branch) => branch,
+            Err(_) => {
+                let remote_name = format!("origin/{}", branch_name);
+                let remote = repo
+                    .find_branch(&remote_name, BranchType::Remote)
+                    .map_err(|_| {
+                        AppError::NotFound(format!("Branch not found: {}", branch_name))
+                    })?;
+                let commit = remote.get().peel_to_commit().map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to get branch tip: {}", e))
+                })?;
+
+                let mut branch = repo.branch(branch_name, &commit, false).map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to create branch: {}", e))
+                })?;
+                branch.set_upstream(Some(&remote_name)).map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to set upstream: {}", e))
+                })?;
+                branch
+            }
+        };
+
+        if !branch.is_head() {
+            let tip = branch.get().target().ok_or_else(|| {
+                AppError::InternalServerError("Failed to get branch tip".to_string())
+            })?;
+            let ref_name = branch.get().name().unwrap_or_default().to_string();
+
+            self.checkout_tree_safely(&repo, tip)?;
+            repo.set_head(&ref_name)
+                .map_err(|e| AppError::InternalServerError(format!("Failed to set HEAD: {}", e)))?;
+
+            info!("Checked out branch {}", branch_name);
+        }
+
+        let branch = repo
+            .find_branch(branch_name, BranchType::Local)
+            .map_err(|_| AppError::NotFound(format!("Branch not found: {}", branch_name)))?;
+        self.branch_info(&repo, &branch, BranchType::Local)
+    }
+}
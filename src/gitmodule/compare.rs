@@ -0,0 +1,92 @@
+use git2::Sort;
+
+use super::GitManager;
+use super::structs::{CompareMode, CompareResult, DiffSettings};
+use crate::shared::error::AppError;
+
+// 比较结果中最多返回的提交数，ahead_by 仍为完整数量
+pub const MAX_COMPARE_COMMITS: usize = 250;
+
+impl GitManager {
+    // 比较两个版本，返回合并基础、head 独有的提交以及树之间的差异
+    pub fn compare_revisions(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        base: &str,
+        head: &str,
+        mode: CompareMode,
+        settings: &DiffSettings,
+    ) -> Result<CompareResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let base_commit = self.resolve_commit(&repo, base)?;
+        let head_commit = self.resolve_commit(&repo, head)?;
+
+        let merge_base = repo.merge_base(base_commit.id(), head_commit.id()).ok();
+        let (ahead_by, behind_by) = repo
+            .graph_ahead_behind(head_commit.id(), base_commit.id())
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to count commits: {}", e))
+            })?;
+
+        // base..head 的提交，从旧到新
+        let mut revwalk = repo.revwalk().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create revwalk: {}", e))
+        })?;
+        revwalk
+            .set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to set sorting: {}", e)))?;
+        revwalk
+            .push(head_commit.id())
+            .and_then(|_| revwalk.hide(base_commit.id()))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to walk commits: {}", e)))?;
+
+        let mut commits = Vec::new();
+        for oid in revwalk.take(MAX_COMPARE_COMMITS) {
+            let oid = oid.map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get commit ID: {}", e))
+            })?;
+            let commit = repo.find_commit(oid).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to find commit: {}", e))
+            })?;
+            commits.push(Self::to_commit_info(&commit));
+        }
+
+        // 两点比较 base 的树，三点比较合并基础的树
+        let from_tree = match mode {
+            CompareMode::TwoDot => base_commit.tree(),
+            CompareMode::ThreeDot => {
+                let merge_base = merge_base.ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "{} and {} have no common ancestor, use two_dot mode",
+                        base, head
+                    ))
+                })?;
+                repo.find_commit(merge_base).and_then(|c| c.tree())
+            }
+        }
+        .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+        let to_tree = head_commit
+            .tree()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+
+        let diff = self.diff_trees(&repo, Some(&from_tree), Some(&to_tree), settings)?;
+        let mut file_changes = Vec::new();
+        let stats =
+            self.process_diff_into_changes(&repo, &diff, &mut file_changes, &settings.limits)?;
+
+        Ok(CompareResult {
+            base: Self::to_commit_info(&base_commit),
+            head: Self::to_commit_info(&head_commit),
+            mode,
+            merge_base: merge_base.map(|oid| oid.to_string()),
+            ahead_by,
+            behind_by,
+            commits,
+            stats,
+            file_changes,
+        })
+    }
+}
@@ -0,0 +1,85 @@
+use git2::{Cred, CredentialType, RemoteCallbacks};
+
+/// 解密后的远程仓库凭据，只在 git 操作期间存在于内存中
+#[derive(Clone)]
+pub enum RemoteCredential {
+    HttpsToken {
+        username: Option<String>,
+        token: String,
+    },
+    SshKey {
+        username: Option<String>,
+        private_key: String,
+        passphrase: Option<String>,
+    },
+}
+
+/// 从远端 URL 中解析主机名，支持 https://、ssh:// 和 git@host:path 形式
+pub fn remote_host(url: &str) -> Option<String> {
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        // scp 形式：git@github.com:owner/repo.git
+        None => url.split_once(':').map(|(host, _)| host)?,
+    };
+
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+/// 构建带认证的远程回调，没有凭据时使用默认回调
+pub fn remote_callbacks(credential: Option<&RemoteCredential>) -> RemoteCallbacks<'_> {
+    let mut callbacks = RemoteCallbacks::new();
+    let Some(credential) = credential else {
+        return callbacks;
+    };
+
+    // 认证失败时 libgit2 会重复调用回调，限制尝试次数避免死循环
+    let mut attempts = 0;
+    callbacks.credentials(move |_url, username_from_url, allowed_types| {
+        // ssh 地址没有用户名时会先单独请求用户名
+        if allowed_types == CredentialType::USERNAME {
+            let username = match credential {
+                RemoteCredential::HttpsToken { username, .. }
+                | RemoteCredential::SshKey { username, .. } => username.as_deref(),
+            };
+            return Cred::username(username.or(username_from_url).unwrap_or("git"));
+        }
+
+        attempts += 1;
+        if attempts > 1 {
+            return Err(git2::Error::from_str("Authentication failed"));
+        }
+
+        match credential {
+            RemoteCredential::HttpsToken { username, token }
+                if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) =>
+            {
+                let username = username
+                    .as_deref()
+                    .or(username_from_url)
+                    .unwrap_or("x-access-token");
+                Cred::userpass_plaintext(username, token)
+            }
+            RemoteCredential::SshKey {
+                username,
+                private_key,
+                passphrase,
+            } if allowed_types.contains(CredentialType::SSH_KEY) => {
+                let username = username.as_deref().or(username_from_url).unwrap_or("git");
+                Cred::ssh_key_from_memory(username, None, private_key, passphrase.as_deref())
+            }
+            _ => Err(git2::Error::from_str(
+                "Stored credential does not match the authentication required by remote",
+            )),
+        }
+    });
+
+    callbacks
+}
@@ -0,0 +1,324 @@
+use git2::{
+    Delta, DiffDelta, DiffFile, DiffFindOptions, DiffOptions, FileMode, Odb, Patch, Repository,
+};
+
+use super::GitManager;
+use super::structs::{
+    CommitFileChange, DiffHunk, DiffLimits, DiffLine, DiffLineKind, DiffSettings, DiffStats,
+    WhitespaceMode,
+};
+use crate::shared::error::AppError;
+
+// 树之间的差异在生成补丁前没有文件大小，从对象头读取；工作区文件的大小来自 stat
+fn file_size(odb: &Odb, file: &DiffFile) -> usize {
+    match file.size() {
+        0 if !file.id().is_zero() => odb.read_header(file.id()).map_or(0, |(size, _)| size),
+        size => size as usize,
+    }
+}
+
+fn delta_status(status: Delta) -> &'static str {
+    match status {
+        Delta::Added => "added",
+        Delta::Deleted => "deleted",
+        Delta::Modified => "modified",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Typechange => "typechange",
+        Delta::Untracked => "untracked",
+        Delta::Conflicted => "conflicted",
+        _ => "changed",
+    }
+}
+
+// 文件不存在一侧的模式为 Unreadable，返回 None
+fn file_mode(mode: FileMode) -> Option<String> {
+    match mode {
+        FileMode::Unreadable => None,
+        mode => Some(format!("{:o}", u32::from(mode))),
+    }
+}
+
+fn delta_path(delta: &DiffDelta, new: bool) -> Option<String> {
+    let file = if new {
+        delta.new_file()
+    } else {
+        delta.old_file()
+    };
+    // 新增文件的旧侧、删除文件的新侧在 git2 中仍带有路径，这里按状态去掉
+    let exists = match delta.status() {
+        Delta::Added | Delta::Untracked => new,
+        Delta::Deleted => !new,
+        _ => true,
+    };
+    if !exists {
+        return None;
+    }
+    file.path().map(|p| p.to_string_lossy().into_owned())
+}
+
+fn line_content(content: &[u8]) -> String {
+    let content = content.strip_suffix(b"\n").unwrap_or(content);
+    let content = content.strip_suffix(b"\r").unwrap_or(content);
+    String::from_utf8_lossy(content).into_owned()
+}
+
+impl GitManager {
+    // 按选项比较两棵树，旧树为空时视为与空树比较
+    pub(super) fn diff_trees<'r>(
+        &self,
+        repo: &'r Repository,
+        old_tree: Option<&git2::Tree>,
+        new_tree: Option<&git2::Tree>,
+        settings: &DiffSettings,
+    ) -> Result<git2::Diff<'r>, AppError> {
+        let mut opts = Self::diff_options(settings);
+        let mut diff = repo
+            .diff_tree_to_tree(old_tree, new_tree, Some(&mut opts))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to compute diff: {}", e)))?;
+        Self::find_similar(&mut diff, settings)?;
+        Ok(diff)
+    }
+
+    pub(super) fn diff_options(settings: &DiffSettings) -> DiffOptions {
+        let mut opts = DiffOptions::new();
+        match settings.whitespace {
+            WhitespaceMode::None => {}
+            WhitespaceMode::IgnoreAll => {
+                opts.ignore_whitespace(true);
+            }
+            WhitespaceMode::IgnoreChange => {
+                opts.ignore_whitespace_change(true);
+            }
+            WhitespaceMode::IgnoreEol => {
+                opts.ignore_whitespace_eol(true);
+            }
+        }
+        if let Some(lines) = settings.context_lines {
+            opts.context_lines(lines);
+        }
+        for path in &settings.pathspec {
+            opts.pathspec(path);
+        }
+        opts
+    }
+
+    // 检测重命名和复制，未开启时保持删除加新增的形式
+    pub(super) fn find_similar(
+        diff: &mut git2::Diff,
+        settings: &DiffSettings,
+    ) -> Result<(), AppError> {
+        if !settings.find_renames && !settings.find_copies {
+            return Ok(());
+        }
+
+        let mut find = DiffFindOptions::new();
+        find.renames(settings.find_renames)
+            .copies(settings.find_copies)
+            .ignore_whitespace(settings.whitespace != WhitespaceMode::None);
+        if let Some(similarity) = settings.similarity {
+            find.rename_threshold(similarity).copy_threshold(similarity);
+        }
+
+        diff.find_similar(Some(&mut find))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to detect renames: {}", e)))
+    }
+
+    // 按文件逐个生成补丁，转换为带行号的结构化变更，返回汇总统计
+    // 超出 limits 的文件不生成 hunks，标记为 truncated
+    pub(super) fn process_diff_into_changes(
+        &self,
+        repo: &Repository,
+        diff: &git2::Diff,
+        file_changes: &mut Vec<CommitFileChange>,
+        limits: &DiffLimits,
+    ) -> Result<DiffStats, AppError> {
+        let odb = repo
+            .odb()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to open odb: {}", e)))?;
+        let mut stats = DiffStats::default();
+
+        for idx in 0..diff.deltas().len() {
+            let Some(delta) = diff.get_delta(idx) else {
+                continue;
+            };
+
+            // 超出文件数限制或文件本身超出字节限制的不再加载内容
+            if idx >= limits.max_files
+                || file_size(&odb, &delta.old_file()) > limits.max_bytes_per_file
+                || file_size(&odb, &delta.new_file()) > limits.max_bytes_per_file
+            {
+                let mut change = Self::delta_to_change(&delta);
+                change.truncated = true;
+                stats.files_changed += 1;
+                stats.truncated = true;
+                file_changes.push(change);
+                continue;
+            }
+
+            let patch = Patch::from_diff(diff, idx).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to build patch: {}", e))
+            })?;
+            let change = match patch {
+                Some(patch) => Self::patch_to_change_limited(&patch, limits)?,
+                // 没有内容差异的条目（例如纯模式变更）
+                None => Self::delta_to_change(&delta),
+            };
+
+            stats.files_changed += 1;
+            stats.additions += change.additions;
+            stats.deletions += change.deletions;
+            stats.truncated |= change.truncated;
+            file_changes.push(change);
+        }
+
+        Ok(stats)
+    }
+
+    fn patch_to_change_limited(
+        patch: &Patch,
+        limits: &DiffLimits,
+    ) -> Result<CommitFileChange, AppError> {
+        let (context, additions, deletions) = patch
+            .line_stats()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to read patch: {}", e)))?;
+        let lines = context + additions + deletions;
+        let bytes = patch.size(true, true, false);
+
+        if lines > limits.max_lines_per_file || bytes > limits.max_bytes_per_file {
+            let mut change = Self::delta_to_change(&patch.delta());
+            change.additions = additions;
+            change.deletions = deletions;
+            change.truncated = true;
+            return Ok(change);
+        }
+
+        Self::patch_to_change(patch)
+    }
+
+    // 获取一次提交中单个文件的完整补丁，path 可以是新路径或重命名前的路径
+    pub fn get_commit_file_diff(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        path: &str,
+        settings: &DiffSettings,
+    ) -> Result<CommitFileChange, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit(&repo, commit_id)?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree().map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get parent tree: {}", e))
+            })?),
+            Err(_) => None,
+        };
+        let commit_tree = commit.tree().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to get commit tree: {}", e))
+        })?;
+
+        // 不按 pathspec 过滤，否则无法识别重命名
+        let settings = DiffSettings {
+            pathspec: Vec::new(),
+            ..settings.clone()
+        };
+        let diff = self.diff_trees(&repo, parent_tree.as_ref(), Some(&commit_tree), &settings)?;
+        Self::file_change_in_diff(&diff, path)?
+            .ok_or_else(|| AppError::NotFound(format!("File not changed in commit: {}", path)))
+    }
+
+    // 在差异中查找新路径或旧路径为 path 的文件，生成完整补丁
+    pub(super) fn file_change_in_diff(
+        diff: &git2::Diff,
+        path: &str,
+    ) -> Result<Option<CommitFileChange>, AppError> {
+        let Some(idx) = diff.deltas().position(|delta| {
+            delta_path(&delta, true).as_deref() == Some(path)
+                || delta_path(&delta, false).as_deref() == Some(path)
+        }) else {
+            return Ok(None);
+        };
+
+        let patch = Patch::from_diff(diff, idx)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to build patch: {}", e)))?;
+        match patch {
+            Some(patch) => Self::patch_to_change(&patch).map(Some),
+            None => Ok(diff
+                .get_delta(idx)
+                .map(|delta| Self::delta_to_change(&delta))),
+        }
+    }
+
+    fn delta_to_change(delta: &DiffDelta) -> CommitFileChange {
+        let old_path = delta_path(delta, false);
+        let new_path = delta_path(delta, true);
+        CommitFileChange {
+            path: new_path
+                .clone()
+                .or_else(|| old_path.clone())
+                .unwrap_or_default(),
+            old_path,
+            new_path,
+            status: delta_status(delta.status()).to_string(),
+            old_mode: file_mode(delta.old_file().mode()),
+            new_mode: file_mode(delta.new_file().mode()),
+            binary: delta.flags().is_binary(),
+            additions: 0,
+            deletions: 0,
+            truncated: false,
+            hunks: Vec::new(),
+        }
+    }
+
+    pub(super) fn patch_to_change(patch: &Patch) -> Result<CommitFileChange, AppError> {
+        let to_err =
+            |e: git2::Error| AppError::InternalServerError(format!("Failed to read patch: {}", e));
+
+        let mut change = Self::delta_to_change(&patch.delta());
+        if change.binary {
+            return Ok(change);
+        }
+
+        let (_, additions, deletions) = patch.line_stats().map_err(to_err)?;
+        change.additions = additions;
+        change.deletions = deletions;
+
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, line_count) = patch.hunk(hunk_idx).map_err(to_err)?;
+            let mut lines = Vec::with_capacity(line_count);
+            for line_idx in 0..line_count {
+                let line = patch.line_in_hunk(hunk_idx, line_idx).map_err(to_err)?;
+                let kind = match line.origin() {
+                    '+' => DiffLineKind::Addition,
+                    '-' => DiffLineKind::Deletion,
+                    '=' | '>' | '<' => DiffLineKind::NoNewline,
+                    _ => DiffLineKind::Context,
+                };
+                // "\ No newline at end of file" 标记前后带有换行
+                let content = match kind {
+                    DiffLineKind::NoNewline => line_content(line.content()).trim().to_string(),
+                    _ => line_content(line.content()),
+                };
+                lines.push(DiffLine {
+                    kind,
+                    old_lineno: line.old_lineno(),
+                    new_lineno: line.new_lineno(),
+                    content,
+                });
+            }
+
+            change.hunks.push(DiffHunk {
+                header: line_content(hunk.header()),
+                old_start: hunk.old_start(),
+                old_lines: hunk.old_lines(),
+                new_start: hunk.new_start(),
+                new_lines: hunk.new_lines(),
+                lines,
+            });
+        }
+
+        Ok(change)
+    }
+}
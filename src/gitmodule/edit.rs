@@ -0,0 +1,479 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use git2::{
+    Commit, ErrorCode, FileMode, ObjectType, Oid, Repository, Signature, Tree,
+    build::TreeUpdateBuilder,
+};
+use tracing::error;
+
+use super::blob::file_content;
+use super::history::{entry_at, same_entry};
+use super::structs::{FileActionKind, FileBatch, FileBatchResult, FileEdit, FileEditResult};
+use super::tree::normalize_tree_path;
+use super::{GitConfig, GitManager};
+use crate::shared::error::AppError;
+
+// 保留原文件的可执行位和符号链接类型，新文件按普通文件处理
+fn edit_mode(filemode: Option<i32>) -> FileMode {
+    match filemode {
+        Some(mode) if mode == i32::from(FileMode::BlobExecutable) => FileMode::BlobExecutable,
+        Some(mode) if mode == i32::from(FileMode::Link) => FileMode::Link,
+        _ => FileMode::Blob,
+    }
+}
+
+// 规范化要写入的路径，不允许空路径段、. 、.. 和 .git
+pub(super) fn edit_path(path: &str) -> Result<String, AppError> {
+    let path = normalize_tree_path(path);
+    if path.is_empty() {
+        return Err(AppError::BadRequest("File path is required".to_string()));
+    }
+    if path
+        .split('/')
+        .any(|part| matches!(part, "" | "." | "..") || part.eq_ignore_ascii_case(".git"))
+    {
+        return Err(AppError::BadRequest(format!("Invalid file path: {}", path)));
+    }
+    Ok(path)
+}
+
+// 批量修改过程中文件的当前状态，先查本批次已修改的结果，再查原来的树，None 表示不存在
+fn file_state(
+    files: &BTreeMap<String, Option<(Oid, FileMode)>>,
+    tree: &Tree,
+    path: &str,
+) -> Result<Option<(Oid, FileMode)>, AppError> {
+    if let Some(state) = files.get(path) {
+        return Ok(*state);
+    }
+    match tree.get_path(Path::new(path)) {
+        Ok(entry) if entry.kind() == Some(ObjectType::Blob) => {
+            Ok(Some((entry.id(), edit_mode(Some(entry.filemode())))))
+        }
+        Ok(_) => Err(AppError::BadRequest(format!(
+            "Path is not a file: {}",
+            path
+        ))),
+        Err(_) => Ok(None),
+    }
+}
+
+impl GitManager {
+    // 修改分支上的单个文件并提交，新树在内存中生成，不会带上索引中其他已暂存的修改
+    // 文件在分支最新提交中的内容与编辑基准不一致时返回 409，附带当前内容供客户端合并
+    // 提交期间分支被其他提交更新时同样返回 409，不会覆盖对方的提交
+    pub fn update_file(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        edit: &FileEdit,
+        committer: &GitConfig,
+    ) -> Result<FileEditResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let path = edit_path(&edit.path)?;
+
+        let (branch_name, ref_name, is_head) = self.target_branch(&repo, edit.branch.as_deref())?;
+        let head = self.resolve_commit(&repo, &ref_name)?;
+        let current = entry_at(&head, &path)?;
+        if current
+            .as_ref()
+            .is_some_and(|e| e.kind() != Some(ObjectType::Blob))
+        {
+            return Err(AppError::BadRequest(format!(
+                "Path is not a file: {}",
+                path
+            )));
+        }
+        let current_id = current.as_ref().map(|e| e.id());
+
+        // 编辑基准中文件的 blob，None 表示新建文件
+        let base_id = match (&edit.base_blob_id, &edit.base_commit_id) {
+            (Some(blob_id), _) => Some(
+                Oid::from_str(blob_id)
+                    .map_err(|_| AppError::BadRequest(format!("Invalid blob id: {}", blob_id)))?,
+            ),
+            (None, Some(commit_id)) => {
+                let base = self.resolve_commit(&repo, commit_id)?;
+                entry_at(&base, &path)?.map(|e| e.id())
+            }
+            (None, None) => {
+                return Err(AppError::BadRequest(
+                    "base_blob_id or base_commit_id is required".to_string(),
+                ));
+            }
+        };
+
+        if base_id != current_id {
+            let current_content = match current_id {
+                Some(id) => Some(file_content(&repo.find_blob(id).map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to get blob: {}", e))
+                })?)),
+                None => None,
+            };
+            return Err(AppError::Conflict(
+                "File has been modified since the edit began".to_string(),
+                serde_json::json!({
+                    "path": path,
+                    "branch": branch_name,
+                    "commit_id": head.id().to_string(),
+                    "current": current_content,
+                }),
+            ));
+        }
+
+        let blob_id = repo
+            .blob(edit.content.as_bytes())
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write blob: {}", e)))?;
+        if Some(blob_id) == current_id {
+            return Err(AppError::BadRequest(format!("No changes to {}", path)));
+        }
+
+        let head_tree = head
+            .tree()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+        let mut builder = TreeUpdateBuilder::new();
+        builder.upsert(&path, blob_id, edit_mode(current.map(|e| e.filemode())));
+        let tree_oid = builder
+            .create_updated(&repo, &head_tree)
+            .map_err(|e| AppError::BadRequest(format!("Failed to update {}: {}", path, e)))?;
+        let tree = repo
+            .find_tree(tree_oid)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to find tree: {}", e)))?;
+
+        let signature = Signature::now(&committer.name, &committer.email).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create signature: {}", e))
+        })?;
+        let commit_id = repo
+            .commit(None, &signature, &signature, &edit.message, &tree, &[&head])
+            .map_err(|e| AppError::InternalServerError(format!("Failed to commit: {}", e)))?;
+
+        // 当前分支的工作区中该文件有未提交的修改时，检出会拒绝覆盖并返回 409
+        self.advance_branch(
+            &repo,
+            &ref_name,
+            Some(&head),
+            commit_id,
+            is_head && !repo.is_bare(),
+            &edit.message,
+        )?;
+
+        Ok(FileEditResult {
+            path,
+            branch: branch_name,
+            blob_id: blob_id.to_string(),
+            commit_id: commit_id.to_string(),
+            previous_commit_id: head.id().to_string(),
+        })
+    }
+
+    // 与 target_branch 相同，但 HEAD 指向尚无提交的分支时也返回该分支，用于向空仓库提交
+    pub(super) fn commit_target(
+        &self,
+        repo: &Repository,
+        branch: Option<&str>,
+    ) -> Result<(String, String, bool), AppError> {
+        let default_branch = branch.map(str::trim).is_none_or(str::is_empty);
+        match repo.head() {
+            Err(e) if e.code() == ErrorCode::UnbornBranch && default_branch => {
+                let head = repo.find_reference("HEAD").map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to get HEAD: {}", e))
+                })?;
+                let ref_name = head
+                    .symbolic_target()
+                    .ok_or_else(|| AppError::BadRequest("HEAD is not a branch".to_string()))?
+                    .to_string();
+                let branch_name = ref_name.trim_start_matches("refs/heads/").to_string();
+                Ok((branch_name, ref_name, true))
+            }
+            _ => self.target_branch(repo, branch),
+        }
+    }
+
+    // 在对象库中直接生成新树并创建一次提交，所有操作全部成功才会移动分支
+    // 默认不改动索引和工作区，也适用于裸仓库
+    pub fn commit_files(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        batch: &FileBatch,
+        committer: &GitConfig,
+    ) -> Result<FileBatchResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        if batch.actions.is_empty() {
+            return Err(AppError::BadRequest(
+                "No file actions to commit".to_string(),
+            ));
+        }
+
+        let (branch_name, ref_name, is_head) =
+            self.commit_target(&repo, batch.branch.as_deref())?;
+        let tip = match repo.find_reference(&ref_name) {
+            Ok(reference) => Some(reference.peel_to_commit().map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get branch commit: {}", e))
+            })?),
+            Err(_) => None,
+        };
+        let base_tree = match &tip {
+            Some(tip) => tip.tree(),
+            None => repo
+                .treebuilder(None)
+                .and_then(|builder| builder.write())
+                .and_then(|oid| repo.find_tree(oid)),
+        }
+        .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+
+        // 先检查涉及的文件在基准提交之后是否被修改过
+        let mut touched = Vec::new();
+        for action in &batch.actions {
+            touched.push(edit_path(&action.path)?);
+            if let Some(previous) = &action.previous_path {
+                touched.push(edit_path(previous)?);
+            }
+        }
+        if let Some(base_commit_id) = &batch.base_commit_id {
+            let base = self.resolve_commit(&repo, base_commit_id)?;
+            let mut modified = Vec::new();
+            for path in &touched {
+                let current = match &tip {
+                    Some(tip) => entry_at(tip, path)?,
+                    None => None,
+                };
+                if !same_entry(&entry_at(&base, path)?, &current) && !modified.contains(path) {
+                    modified.push(path.clone());
+                }
+            }
+            if !modified.is_empty() {
+                return Err(AppError::Conflict(
+                    "Files have been modified since the base commit".to_string(),
+                    serde_json::json!({
+                        "branch": branch_name,
+                        "commit_id": tip.as_ref().map(|t| t.id().to_string()),
+                        "paths": modified,
+                    }),
+                ));
+            }
+        }
+
+        let write_blob = |content: &str| {
+            repo.blob(content.as_bytes())
+                .map_err(|e| AppError::InternalServerError(format!("Failed to write blob: {}", e)))
+        };
+        let not_found = |path: &str| AppError::NotFound(format!("File not found: {}", path));
+        let exists = |path: &str| AppError::BadRequest(format!("File already exists: {}", path));
+
+        // 按顺序应用每个操作，记录每个路径的最终状态
+        let mut files: BTreeMap<String, Option<(Oid, FileMode)>> = BTreeMap::new();
+        for action in &batch.actions {
+            let path = edit_path(&action.path)?;
+            let current = file_state(&files, &base_tree, &path)?;
+            let content = || match (action.blob_id, &action.content) {
+                (Some(id), _) => Ok(id),
+                (None, Some(content)) => write_blob(content),
+                (None, None) => Err(AppError::BadRequest(format!(
+                    "content is required for {}",
+                    path
+                ))),
+            };
+
+            let state = match action.action {
+                FileActionKind::Create => {
+                    if current.is_some() {
+                        return Err(exists(&path));
+                    }
+                    let mode = match action.executable {
+                        Some(true) => FileMode::BlobExecutable,
+                        _ => FileMode::Blob,
+                    };
+                    Some((content()?, mode))
+                }
+                FileActionKind::Update => {
+                    let (_, mode) = current.ok_or_else(|| not_found(&path))?;
+                    Some((content()?, mode))
+                }
+                FileActionKind::Delete => {
+                    current.ok_or_else(|| not_found(&path))?;
+                    None
+                }
+                FileActionKind::Move => {
+                    let previous = action.previous_path.as_deref().ok_or_else(|| {
+                        AppError::BadRequest(format!("previous_path is required for {}", path))
+                    })?;
+                    let previous = edit_path(previous)?;
+                    let (id, mode) = file_state(&files, &base_tree, &previous)?
+                        .ok_or_else(|| not_found(&previous))?;
+                    if previous != path && current.is_some() {
+                        return Err(exists(&path));
+                    }
+                    let id = match (action.blob_id, &action.content) {
+                        (None, None) => id,
+                        _ => content()?,
+                    };
+                    files.insert(previous, None);
+                    Some((id, mode))
+                }
+                FileActionKind::Chmod => {
+                    let (id, mode) = current.ok_or_else(|| not_found(&path))?;
+                    let executable = action.executable.ok_or_else(|| {
+                        AppError::BadRequest(format!("executable is required for {}", path))
+                    })?;
+                    if mode == FileMode::Link {
+                        return Err(AppError::BadRequest(format!(
+                            "Cannot chmod a symbolic link: {}",
+                            path
+                        )));
+                    }
+                    match executable {
+                        true => Some((id, FileMode::BlobExecutable)),
+                        false => Some((id, FileMode::Blob)),
+                    }
+                }
+            };
+            files.insert(path, state);
+        }
+
+        let mut builder = TreeUpdateBuilder::new();
+        for (path, state) in &files {
+            match state {
+                Some((id, mode)) => {
+                    builder.upsert(path, *id, *mode);
+                }
+                // 本批次中新建后又删除的文件不在原树中
+                None => {
+                    if base_tree.get_path(Path::new(path)).is_ok() {
+                        builder.remove(path);
+                    }
+                }
+            }
+        }
+        let tree_oid = builder
+            .create_updated(&repo, &base_tree)
+            .map_err(|e| AppError::BadRequest(format!("Failed to build tree: {}", e)))?;
+        if tip.as_ref().is_some_and(|t| t.tree_id() == tree_oid) {
+            return Err(AppError::BadRequest("No changes to commit".to_string()));
+        }
+        let tree = repo
+            .find_tree(tree_oid)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to find tree: {}", e)))?;
+
+        let signature = Signature::now(&committer.name, &committer.email).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create signature: {}", e))
+        })?;
+        let parents: Vec<&Commit> = tip.iter().collect();
+        let commit_id = repo
+            .commit(
+                None,
+                &signature,
+                &signature,
+                &batch.message,
+                &tree,
+                &parents,
+            )
+            .map_err(|e| AppError::InternalServerError(format!("Failed to commit: {}", e)))?;
+
+        self.advance_branch(
+            &repo,
+            &ref_name,
+            tip.as_ref(),
+            commit_id,
+            is_head && batch.update_workdir && !repo.is_bare(),
+            &batch.message,
+        )?;
+
+        Ok(FileBatchResult {
+            branch: branch_name,
+            commit_id: commit_id.to_string(),
+            previous_commit_id: tip.map(|t| t.id().to_string()),
+        })
+    }
+
+    // 把分支从 tip 移到新提交，checkout 为 true 时先检出新提交的树
+    // 只在分支仍指向 tip 时更新，避免覆盖并发的提交，tip 为 None 表示分支尚不存在
+    // 检出不移动 HEAD，分支更新失败时把检出的文件恢复为 tip 中的内容
+    fn advance_branch(
+        &self,
+        repo: &Repository,
+        ref_name: &str,
+        tip: Option<&Commit>,
+        commit_id: Oid,
+        checkout: bool,
+        message: &str,
+    ) -> Result<(), AppError> {
+        if checkout {
+            self.checkout_tree_safely(repo, commit_id)?;
+        }
+
+        let log_message = format!("commit: {}", message.lines().next().unwrap_or(""));
+        let updated = match tip {
+            Some(tip) => repo.reference_matching(ref_name, commit_id, true, tip.id(), &log_message),
+            None => repo.reference(ref_name, commit_id, false, &log_message),
+        };
+        if let Err(e) = updated {
+            // 恢复失败只记录日志，仍然返回分支更新失败的原因
+            if checkout {
+                Self::restore_checkout(repo, tip, commit_id).unwrap_or_else(|restore| {
+                    error!("Restoring workdir of {} failed: {:?}", ref_name, restore)
+                });
+            }
+            return Err(match e.code() {
+                // 引用文件被另一次提交锁定时同样视为并发更新
+                ErrorCode::Modified | ErrorCode::Exists | ErrorCode::Locked => AppError::Conflict(
+                    "Branch was updated by another commit".to_string(),
+                    serde_json::json!({
+                        "branch": ref_name.strip_prefix("refs/heads/").unwrap_or(ref_name),
+                    }),
+                ),
+                _ => AppError::InternalServerError(format!("Failed to update reference: {}", e)),
+            });
+        }
+
+        Ok(())
+    }
+
+    // 撤销 advance_branch 中的检出，只恢复 tip 与新提交之间改动的文件
+    // 检出前这些文件与 tip 一致（否则检出会被拒绝），强制恢复不会丢失本地修改
+    fn restore_checkout(
+        repo: &Repository,
+        tip: Option<&Commit>,
+        commit_id: Oid,
+    ) -> Result<(), AppError> {
+        let restore_err = |e: git2::Error| {
+            AppError::InternalServerError(format!("Failed to restore workdir: {}", e))
+        };
+        let old_tree = match tip {
+            Some(tip) => tip.tree(),
+            None => repo
+                .treebuilder(None)
+                .and_then(|builder| builder.write())
+                .and_then(|oid| repo.find_tree(oid)),
+        }
+        .map_err(restore_err)?;
+        let new_tree = repo
+            .find_commit(commit_id)
+            .and_then(|commit| commit.tree())
+            .map_err(restore_err)?;
+        let diff = repo
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+            .map_err(restore_err)?;
+        if diff.deltas().len() == 0 {
+            return Ok(());
+        }
+
+        let mut checkout_opts = git2::build::CheckoutBuilder::new();
+        checkout_opts.force().remove_untracked(true);
+        for delta in diff.deltas() {
+            for path in [delta.old_file().path(), delta.new_file().path()]
+                .into_iter()
+                .flatten()
+            {
+                checkout_opts.path(path);
+            }
+        }
+        repo.checkout_tree(old_tree.as_object(), Some(&mut checkout_opts))
+            .map_err(restore_err)
+    }
+}
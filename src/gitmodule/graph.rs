@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use git2::{Oid, Repository, Sort};
+
+use super::GitManager;
+use super::history::page_offset;
+use super::structs::{CommitGraphPage, GraphEdge, GraphNode};
+use crate::shared::error::AppError;
+
+// 每个 lane 记录下一行期望出现的提交，空出的 lane 留给后面的分支复用
+struct Lanes {
+    slots: Vec<Option<Oid>>,
+}
+
+impl Lanes {
+    fn find(&self, id: Oid) -> Option<usize> {
+        self.slots.iter().position(|slot| *slot == Some(id))
+    }
+
+    fn alloc(&mut self, id: Oid) -> usize {
+        match self.slots.iter().position(|slot| slot.is_none()) {
+            Some(column) => {
+                self.slots[column] = Some(id);
+                column
+            }
+            None => {
+                self.slots.push(Some(id));
+                self.slots.len() - 1
+            }
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.slots.last() == Some(&None) {
+            self.slots.pop();
+        }
+    }
+}
+
+// 所有分支和标签按指向的提交分组，用作节点上的标签
+fn ref_labels(repo: &Repository) -> Result<HashMap<Oid, Vec<String>>, AppError> {
+    let mut labels: HashMap<Oid, Vec<String>> = HashMap::new();
+    let references = repo
+        .references()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list references: {}", e)))?;
+    for reference in references.flatten() {
+        if reference.is_remote() && reference.shorthand().is_some_and(|s| s.ends_with("/HEAD")) {
+            continue;
+        }
+        let (Some(name), Ok(commit)) = (reference.shorthand(), reference.peel_to_commit()) else {
+            continue;
+        };
+        labels
+            .entry(commit.id())
+            .or_default()
+            .push(name.to_string());
+    }
+    if let Ok(head) = repo.head().and_then(|h| h.peel_to_commit()) {
+        labels
+            .entry(head.id())
+            .or_default()
+            .insert(0, "HEAD".to_string());
+    }
+    Ok(labels)
+}
+
+impl GitManager {
+    // 按拓扑顺序遍历多个版本的提交，计算 git log --graph 风格的列和连线
+    // 列的分配依赖前面所有行，因此每次请求都从头计算，只返回请求的那一页
+    pub fn get_commit_graph(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        refs: &[String],
+        cursor: Option<&str>,
+        limit: usize,
+        page: usize,
+    ) -> Result<CommitGraphPage, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let mut revwalk = repo.revwalk().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create revwalk: {}", e))
+        })?;
+        revwalk
+            .set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to set sorting: {}", e)))?;
+
+        // 未指定时使用 HEAD 和所有本地分支
+        let walk_err = |e: git2::Error| {
+            AppError::InternalServerError(format!("Failed to walk commits: {}", e))
+        };
+        if refs.is_empty() {
+            let head = self.resolve_commit_or_head(&repo, None)?;
+            revwalk.push(head.id()).map_err(walk_err)?;
+            revwalk.push_glob("refs/heads").map_err(walk_err)?;
+        }
+        for rev in refs {
+            let commit = self.resolve_commit(&repo, rev)?;
+            revwalk.push(commit.id()).map_err(walk_err)?;
+        }
+
+        let cursor = cursor
+            .map(|c| {
+                Oid::from_str(c).map_err(|_| AppError::BadRequest(format!("Invalid cursor: {}", c)))
+            })
+            .transpose()?;
+        let skip_count = match cursor {
+            Some(_) => 0,
+            None => page_offset(page, limit)?,
+        };
+
+        let labels = ref_labels(&repo)?;
+        let mut lanes = Lanes { slots: Vec::new() };
+        // 上一行到当前行的连线，要等当前行的列确定后才能定下来
+        let mut previous_edges: Vec<GraphEdge> = Vec::new();
+        let mut previous_node: Option<GraphNode> = None;
+        let mut nodes = Vec::new();
+        let mut found_cursor = cursor.is_none();
+        let mut has_more = false;
+
+        for (index, oid) in revwalk.enumerate() {
+            let oid = oid.map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get commit ID: {}", e))
+            })?;
+
+            // 等待该提交的多个 lane 汇合到同一列
+            let column = lanes.find(oid).unwrap_or_else(|| lanes.alloc(oid));
+            for (lane, slot) in lanes.slots.iter_mut().enumerate() {
+                if *slot == Some(oid) && lane != column {
+                    *slot = None;
+                    for edge in previous_edges.iter_mut().filter(|e| e.to_column == lane) {
+                        edge.to_column = column;
+                    }
+                }
+            }
+            if let Some(mut node) = previous_node.take() {
+                node.edges = std::mem::take(&mut previous_edges);
+                nodes.push(node);
+            }
+            if nodes.len() >= limit {
+                has_more = true;
+                break;
+            }
+
+            let commit = repo.find_commit(oid).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to find commit: {}", e))
+            })?;
+
+            // 其他已有的 lane 竖直穿过这一行
+            let mut edges: Vec<GraphEdge> = lanes
+                .slots
+                .iter()
+                .enumerate()
+                .filter(|(lane, slot)| slot.is_some() && *lane != column)
+                .map(|(lane, _)| GraphEdge {
+                    from_column: lane,
+                    to_column: lane,
+                })
+                .collect();
+
+            // 第一个父提交沿用当前列，其余父提交复用已有的 lane 或分配新的 lane
+            let parents: Vec<Oid> = commit.parent_ids().collect();
+            lanes.slots[column] = parents.first().copied();
+            for (i, parent) in parents.iter().enumerate() {
+                let to_column = match i {
+                    0 => column,
+                    _ => lanes.find(*parent).unwrap_or_else(|| lanes.alloc(*parent)),
+                };
+                edges.push(GraphEdge {
+                    from_column: column,
+                    to_column,
+                });
+            }
+            lanes.trim();
+            previous_edges = edges;
+
+            if !found_cursor {
+                found_cursor = Some(oid) == cursor;
+                continue;
+            }
+            if index < skip_count {
+                continue;
+            }
+
+            previous_node = Some(GraphNode {
+                commit_info: Self::to_commit_info(&commit),
+                parents: parents.iter().map(|p| p.to_string()).collect(),
+                column,
+                edges: Vec::new(),
+                refs: labels.get(&oid).cloned().unwrap_or_default(),
+            });
+        }
+        if let Some(mut node) = previous_node.take() {
+            node.edges = previous_edges;
+            nodes.push(node);
+        }
+
+        if !found_cursor {
+            return Err(AppError::BadRequest(format!(
+                "Invalid cursor: {}",
+                cursor.map(|c| c.to_string()).unwrap_or_default()
+            )));
+        }
+
+        let next_cursor = match nodes.last() {
+            Some(last) if has_more => Some(last.commit_info.id.clone()),
+            _ => None,
+        };
+
+        Ok(CommitGraphPage { nodes, next_cursor })
+    }
+}
@@ -0,0 +1,270 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::path::Path;
+
+use git2::{Commit, Delta, DiffFindOptions, ObjectType, Oid, Repository, TreeEntry};
+
+use super::GitManager;
+use super::structs::FileHistoryEntry;
+use super::tree::normalize_tree_path;
+use crate::shared::error::AppError;
+
+// 按提交时间排序的待遍历提交，时间相同时先入队的先出队
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct Pending {
+    pub time: i64,
+    order: Reverse<u64>,
+    pub id: Oid,
+}
+
+// 按提交时间从新到旧遍历的队列，与 git log 的默认顺序一致，已入队过的提交不再入队
+// 时间相同时按入队顺序出队，同一秒内连续创建的提交（如 rebase）不会先于其子提交出队
+pub(super) struct CommitQueue {
+    heap: BinaryHeap<Pending>,
+    seen: HashSet<Oid>,
+    next_order: u64,
+}
+
+impl CommitQueue {
+    pub(super) fn new() -> Self {
+        CommitQueue {
+            heap: BinaryHeap::new(),
+            seen: HashSet::new(),
+            next_order: 0,
+        }
+    }
+
+    pub(super) fn push(&mut self, commit: &Commit) {
+        if self.seen.insert(commit.id()) {
+            self.heap.push(Pending {
+                time: commit.time().seconds(),
+                order: Reverse(self.next_order),
+                id: commit.id(),
+            });
+            self.next_order += 1;
+        }
+    }
+
+    pub(super) fn pop(&mut self) -> Option<Pending> {
+        self.heap.pop()
+    }
+
+    // 放回刚出队的提交，保持原来的顺序
+    pub(super) fn restore(&mut self, pending: Pending) {
+        self.heap.push(pending);
+    }
+
+    // 按出队顺序排列的剩余提交
+    fn into_ids(self) -> Vec<Oid> {
+        let mut pending = self.heap.into_sorted_vec();
+        pending.reverse();
+        pending.into_iter().map(|p| p.id).collect()
+    }
+}
+
+// 按页码翻页时需要跳过的条目数，页码过大导致溢出时返回 400
+pub fn page_offset(page: usize, limit: usize) -> Result<usize, AppError> {
+    page.checked_sub(1)
+        .and_then(|p| p.checked_mul(limit))
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid page: {}", page)))
+}
+
+// 游标中最多保存的待遍历提交数，约 8KB，可以放进查询字符串
+const MAX_CURSOR_COMMITS: usize = 200;
+
+// 翻页游标：下一页从这些待遍历的提交继续遍历，按出队顺序以逗号分隔
+pub(super) fn encode_cursor(queue: CommitQueue) -> Result<String, AppError> {
+    if queue.heap.len() > MAX_CURSOR_COMMITS {
+        return Err(AppError::BadRequest(
+            "History has too many parallel branches to page with a cursor, use page instead"
+                .to_string(),
+        ));
+    }
+    Ok(queue
+        .into_ids()
+        .iter()
+        .map(Oid::to_string)
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+pub(super) fn decode_cursor<'r>(
+    repo: &'r Repository,
+    cursor: &str,
+) -> Result<Vec<Commit<'r>>, AppError> {
+    let invalid = || AppError::BadRequest(format!("Invalid cursor: {}", cursor));
+    let ids: Vec<&str> = cursor.split(',').collect();
+    if ids.len() > MAX_CURSOR_COMMITS {
+        return Err(invalid());
+    }
+    ids.into_iter()
+        .map(|id| {
+            Oid::from_str(id)
+                .and_then(|oid| repo.find_commit(oid))
+                .map_err(|_| invalid())
+        })
+        .collect()
+}
+
+pub(super) fn entry_at(
+    commit: &Commit,
+    path: &str,
+) -> Result<Option<TreeEntry<'static>>, AppError> {
+    let tree = commit
+        .tree()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+    Ok(tree.get_path(Path::new(path)).ok())
+}
+
+pub(super) fn same_entry(a: &Option<TreeEntry>, b: &Option<TreeEntry>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.id() == b.id() && a.filemode() == b.filemode(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+// 与所有父提交在该路径上都不同时视为修改了该路径，根提交中存在即视为新增
+pub(super) fn touches_path(commit: &Commit, path: &str) -> Result<bool, AppError> {
+    let current = entry_at(commit, path)?;
+    if commit.parent_count() == 0 {
+        return Ok(current.is_some());
+    }
+    for parent in commit.parents() {
+        if same_entry(&current, &entry_at(&parent, path)?) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+impl GitManager {
+    // 列出修改过指定文件或目录的提交，从新到旧
+    // 与 git log 相同，合并提交若与某个父提交在该路径上一致，只沿该父提交继续，且不计入结果
+    // 路径是文件时与 git log --follow 相同，遇到新增会检测是否由重命名而来，之后按旧路径继续查找
+    pub fn get_file_history(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        path: &str,
+        rev: Option<&str>,
+        limit: usize,
+        page: usize,
+    ) -> Result<Vec<FileHistoryEntry>, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let start = self.resolve_commit_or_head(&repo, rev)?;
+        let mut path = normalize_tree_path(path);
+        if path.is_empty() {
+            return Err(AppError::BadRequest("File path is required".to_string()));
+        }
+
+        // 目录不跟踪重命名
+        let follow = entry_at(&start, &path)?.is_none_or(|e| e.kind() == Some(ObjectType::Blob));
+
+        let skip_count = page_offset(page, limit)?;
+        let mut matched = 0;
+        let mut history = Vec::new();
+
+        let mut queue = CommitQueue::new();
+        queue.push(&start);
+
+        while let Some(Pending { id, .. }) = queue.pop() {
+            if history.len() >= limit {
+                break;
+            }
+
+            let commit = repo.find_commit(id).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to find commit: {}", e))
+            })?;
+            let current = entry_at(&commit, &path)?;
+
+            let mut parents = Vec::new();
+            let mut same_parent = None;
+            for parent in commit.parents() {
+                let entry = entry_at(&parent, &path)?;
+                if same_parent.is_none() && same_entry(&current, &entry) {
+                    same_parent = Some(parent.clone());
+                }
+                parents.push((parent, entry));
+            }
+
+            // 与某个父提交一致时该提交没有修改此路径，只沿该父提交继续
+            if let Some(parent) = same_parent {
+                queue.push(&parent);
+                continue;
+            }
+
+            let first_parent = parents.first().map(|(p, e)| (p, e.is_some()));
+            let (status, old_path) = match (&current, first_parent) {
+                (None, _) => ("deleted", None),
+                (Some(_), Some((_, true))) => ("modified", None),
+                (Some(_), Some((parent, false))) if follow && parents.len() == 1 => {
+                    match self.find_rename_source(&repo, parent, &commit, &path)? {
+                        Some(old) => ("renamed", Some(old)),
+                        None => ("added", None),
+                    }
+                }
+                (Some(_), _) => ("added", None),
+            };
+
+            // 根提交中不存在该路径时不计入结果
+            if current.is_some() || !parents.is_empty() {
+                if matched >= skip_count {
+                    history.push(FileHistoryEntry {
+                        commit_info: Self::to_commit_info(&commit),
+                        path: path.clone(),
+                        old_path: old_path.clone(),
+                        status: status.to_string(),
+                    });
+                }
+                matched += 1;
+            }
+
+            // 重命名之前的提交按旧路径查找
+            if let Some(old) = old_path {
+                path = old;
+            }
+
+            for (parent, _) in parents {
+                queue.push(&parent);
+            }
+        }
+
+        Ok(history)
+    }
+
+    // 比较父提交和当前提交，查找重命名到 path 的文件原路径
+    fn find_rename_source(
+        &self,
+        repo: &Repository,
+        parent: &Commit,
+        commit: &Commit,
+        path: &str,
+    ) -> Result<Option<String>, AppError> {
+        let to_err = |e: git2::Error| {
+            AppError::InternalServerError(format!("Failed to compute diff: {}", e))
+        };
+
+        let old_tree = parent.tree().map_err(to_err)?;
+        let new_tree = commit.tree().map_err(to_err)?;
+        let mut diff = repo
+            .diff_tree_to_tree(Some(&old_tree), Some(&new_tree), None)
+            .map_err(to_err)?;
+        let mut find = DiffFindOptions::new();
+        find.renames(true);
+        diff.find_similar(Some(&mut find)).map_err(to_err)?;
+
+        Ok(diff
+            .deltas()
+            .filter(|delta| delta.status() == Delta::Renamed)
+            .find(|delta| delta.new_file().path() == Some(Path::new(path)))
+            .and_then(|delta| {
+                delta
+                    .old_file()
+                    .path()
+                    .map(|p| p.to_string_lossy().into_owned())
+            }))
+    }
+}
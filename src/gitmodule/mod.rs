@@ -5,14 +5,34 @@ use std::{
 
 use crate::{shared::error::AppError, vos::ReposVo};
 // use axum::extract::Path;
-use git2::{
-    Cred, FetchOptions, IndexAddOption, RemoteCallbacks, Repository, Signature, build::RepoBuilder,
-};
+use credential::{RemoteCredential, remote_callbacks};
+use git2::{FetchOptions, IndexAddOption, Repository, Signature, build::RepoBuilder};
 use serde::{Deserialize, Serialize};
-use structs::{CommitDetail, CommitFileChange, CommitInfo, GitFileEntry, WebSocketManager};
+use structs::{
+    CommitDetail, CommitFilter, CommitHistoryPage, CommitInfo, ConflictEntry, DiffSettings,
+    GitFileEntry, MergeFilter, PullOutcome, PullResult, PullStrategy, PushRefStatus, PushResult,
+    WebSocketManager,
+};
 use tracing::info;
 
+pub mod archive;
+pub mod blame;
+pub mod blob;
+pub mod branch;
+pub mod compare;
+pub mod credential;
+pub mod diff;
+pub mod edit;
+pub mod graph;
+pub mod history;
+pub mod reset;
+pub mod revert;
+pub mod stash;
+pub mod status;
 pub mod structs;
+pub mod tag;
+pub mod tree;
+pub mod upload;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct GitConfig {
@@ -66,6 +86,7 @@ impl GitManager {
         user_id: &str,
         repo_url: &str,
         repo_name: &str,
+        credential: Option<RemoteCredential>,
         ws_manager: &WebSocketManager,
     ) -> Result<String, AppError> {
         let user_path = self.ensure_user_directory(user_id)?;
@@ -95,7 +116,11 @@ impl GitManager {
         // 使用tokio::spawn在后台执行，不等待其完成
         tokio::spawn(async move {
             let result = tokio::task::spawn_blocking(move || {
+                let mut fetch_opts = FetchOptions::new();
+                fetch_opts.remote_callbacks(remote_callbacks(credential.as_ref()));
+
                 let result = RepoBuilder::new()
+                    .fetch_options(fetch_opts)
                     .clone(&repo_url, &repo_path_clone)
                     .map_err(|e| e)
                     .map(|_| repo_path_clone.to_string_lossy().to_string());
@@ -159,14 +184,79 @@ impl GitManager {
             .map_err(|e| AppError::InternalServerError(format!("Failed to open repository: {}", e)))
     }
 
+    // 将分支名、标签、提交 ID、HEAD~3、main@{yesterday} 等任意 revspec 解析为提交
+    // 同名时本地分支优先
+    fn resolve_commit<'r>(
+        &self,
+        repo: &'r Repository,
+        rev: &str,
+    ) -> Result<git2::Commit<'r>, AppError> {
+        let rev = rev.trim();
+        if rev.is_empty() {
+            return Err(AppError::BadRequest("Revision is empty".into()));
+        }
+
+        let object = match repo.find_reference(&format!("refs/heads/{}", rev)) {
+            Ok(reference) => reference.peel(git2::ObjectType::Any),
+            Err(_) => repo.revparse_single(rev),
+        }
+        .map_err(|e| match e.code() {
+            git2::ErrorCode::NotFound => AppError::NotFound(format!("Revision not found: {}", rev)),
+            git2::ErrorCode::Ambiguous => AppError::BadRequest(format!(
+                "Ambiguous revision: {}, use a longer commit id",
+                rev
+            )),
+            git2::ErrorCode::InvalidSpec => {
+                AppError::BadRequest(format!("Invalid revision: {}: {}", rev, e.message()))
+            }
+            _ => AppError::BadRequest(format!("Failed to resolve {}: {}", rev, e.message())),
+        })?;
+
+        object.peel_to_commit().map_err(|_| {
+            AppError::BadRequest(format!(
+                "Revision {} does not point to a commit ({})",
+                rev,
+                object.kind().map(|k| k.str()).unwrap_or("unknown")
+            ))
+        })
+    }
+
+    // 解析 rev，未指定时使用 HEAD
+    fn resolve_commit_or_head<'r>(
+        &self,
+        repo: &'r Repository,
+        rev: Option<&str>,
+    ) -> Result<git2::Commit<'r>, AppError> {
+        match rev {
+            Some(rev) => self.resolve_commit(repo, rev),
+            None => repo
+                .head()
+                .and_then(|head| head.peel_to_commit())
+                .map_err(|e| AppError::InternalServerError(format!("Failed to get HEAD: {}", e))),
+        }
+    }
+
+    fn to_commit_info(commit: &git2::Commit) -> CommitInfo {
+        CommitInfo {
+            id: commit.id().to_string(),
+            author: format!(
+                "{} <{}>",
+                commit.author().name().unwrap_or(""),
+                commit.author().email().unwrap_or("")
+            ),
+            message: commit.message().unwrap_or("").to_string(),
+            time: commit.time().seconds(),
+        }
+    }
+
     pub fn commit_for_user(
         &self,
         user_id: &str,
         repo_name: &str,
         message: &str,
         paths: &[&str],
-        // user_name: &str,  // 用户姓名，通常是 username
-        user_email: &str, // 用户邮箱
+        committer: &GitConfig,      // 提交者，即当前登录用户
+        author: Option<&GitConfig>, // 可选的作者覆盖，默认与提交者相同
     ) -> Result<String, AppError> {
         let repo_path = self.get_user_repo_path(user_id, repo_name);
         let repo = self.open_repo(&repo_path)?;
@@ -183,13 +273,22 @@ impl GitManager {
                 .map_err(|e| {
                     AppError::InternalServerError(format!("Failed to add files: {}", e))
                 })?;
+            // 同步已删除的文件
+            index.update_all(["*"].iter(), None).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to update index: {}", e))
+            })?;
         } else {
-            // 添加特定文件
-
+            // 添加特定文件，工作区中已不存在的文件从索引中移除
             for path in paths {
-                index.add_path(Path::new(path)).map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to add {}: {}", path, e))
-                })?;
+                if repo_path.join(path).exists() {
+                    index.add_path(Path::new(path)).map_err(|e| {
+                        AppError::InternalServerError(format!("Failed to add {}: {}", path, e))
+                    })?;
+                } else {
+                    index.remove_path(Path::new(path)).map_err(|e| {
+                        AppError::BadRequest(format!("Failed to stage {}: {}", path, e))
+                    })?;
+                }
             }
         }
 
@@ -207,9 +306,14 @@ impl GitManager {
         // let signature = Signature::now(&self.config.name, &self.config.email).map_err(|e| {
         //     AppError::InternalServerError(format!("Failed to create signature: {}", e))
         // })?;
-        let signature = Signature::now(user_id, user_email).map_err(|e| {
+        let committer_sig = Signature::now(&committer.name, &committer.email).map_err(|e| {
             AppError::InternalServerError(format!("Failed to create signature: {}", e))
         })?;
+        let author_sig = match author {
+            Some(author) => Signature::now(&author.name, &author.email)
+                .map_err(|e| AppError::BadRequest(format!("Invalid author: {}", e)))?,
+            None => committer_sig.clone(),
+        };
 
         let tree = repo
             .find_tree(oid)
@@ -251,6 +355,11 @@ impl GitManager {
             }
         };
 
+        // 索引与父提交的树相同，说明没有可提交的更改
+        if parent_commit.as_ref().is_some_and(|c| c.tree_id() == oid) {
+            return Err(AppError::BadRequest("Nothing to commit".to_string()));
+        }
+
         // Parents slice needs to be &[&Commit]
         let parents_vec: Vec<&git2::Commit> =
             parent_commit.as_ref().map_or(Vec::new(), |c| vec![c]);
@@ -260,8 +369,8 @@ impl GitManager {
         let commit_id = repo
             .commit(
                 Some("HEAD"),
-                &signature,
-                &signature,
+                &author_sig,
+                &committer_sig,
                 message,
                 &tree,
                 parents_slice,
@@ -271,90 +380,159 @@ impl GitManager {
         Ok(commit_id.to_string())
     }
 
+    // 解析版本对应的提交 ID，用作按 ref 指向缓存的键
+    pub fn resolve_commit_id(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        rev: Option<&str>,
+    ) -> Result<String, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+        let commit = self.resolve_commit_or_head(&repo, rev)?;
+        Ok(commit.id().to_string())
+    }
+
+    // 统计从指定提交可达的提交数
     pub fn get_total_commits_count(
         &self,
         user_id: &str,
         repo_name: &str,
+        commit_id: &str,
     ) -> Result<usize, AppError> {
         let repo_path = self.get_user_repo_path(user_id, repo_name);
         let repo = self.open_repo(&repo_path)?;
+        let commit = self.resolve_commit(&repo, commit_id)?;
 
         let mut revwalk = repo.revwalk().map_err(|e| {
             AppError::InternalServerError(format!("Failed to create revwalk: {}", e))
         })?;
 
         revwalk
-            .push_head()
+            .push(commit.id())
             .map_err(|e| AppError::InternalServerError(format!("Failed to push head: {}", e)))?;
         let count = revwalk.count();
         info!("count is {}", count);
         Ok(count)
     }
 
+    // 与 git log 相同按提交时间从新到旧遍历，父提交在其子提交输出后才进入队列
+    // cursor 记录上一页结束时的遍历状态，翻页时从这里继续，不需要重新跳过前面的页
+    // 没有 cursor 时按 page 跳过前面的记录
     pub fn get_commit_histories(
         &self,
         user_id: &str,
         repo_name: &str,
+        filter: &CommitFilter,
+        cursor: Option<&str>,
         limit: usize,
         page: usize,
-        total_count: usize,
-    ) -> Result<Vec<CommitInfo>, AppError> {
+    ) -> Result<CommitHistoryPage, AppError> {
         // bsaepath/user_id/repo_name
         let repo_path = self.base_path.join(user_id).join(repo_name);
         let repo = self.open_repo(&repo_path)?;
 
-        let mut revwalk = repo.revwalk().map_err(|e| {
-            AppError::InternalServerError(format!("Failed to create revwalk: {}", e))
-        })?;
-
-        if (page - 1) * limit > total_count {
-            return Err(AppError::NotFound(format!(
-                "Page {} exceeds total records. Total commits: {}, page size: {}",
-                page, total_count, limit
-            )));
+        let starts = match cursor {
+            Some(cursor) => history::decode_cursor(&repo, cursor)?,
+            None => vec![self.resolve_commit_or_head(&repo, filter.rev.as_deref())?],
+        };
+        // 下一页从剩余的待遍历提交继续，不用从头遍历
+        let mut queue = history::CommitQueue::new();
+        for commit in &starts {
+            queue.push(commit);
         }
 
-        revwalk
-            .set_sorting(git2::Sort::TIME)
-            .map_err(|e| AppError::InternalServerError(format!("Failed to set sorting: {}", e)))?;
+        let path = filter
+            .path
+            .as_deref()
+            .map(tree::normalize_tree_path)
+            .filter(|p| !p.is_empty());
 
-        revwalk
-            .push_head()
-            .map_err(|e| AppError::InternalServerError(format!("Failed to push head: {}", e)))?;
-
-        // let start_index = (page - 1) * limit;
-        // let end_index = start_index + limit;
-        let skip_count = (page - 1) * limit;
+        let skip_count = match cursor {
+            Some(_) => 0,
+            None => history::page_offset(page, limit)?,
+        };
+        let mut matched = 0;
         let mut commits = Vec::new();
-        for (i, oid) in revwalk.enumerate().skip(skip_count).take(limit) {
-            // if i < start_index {
-            //     continue;
-            // }
-
-            // // 达到页末，结束遍历
-            // if i >= end_index {
-            //     break;
-            // }
-            let oid = oid.map_err(|e| {
-                AppError::InternalServerError(format!("Failed to get commit ID: {}", e))
-            })?;
-            let commit = repo.find_commit(oid).map_err(|e| {
+        let mut has_more = false;
+        while let Some(pending) = queue.pop() {
+            let commit = repo.find_commit(pending.id).map_err(|e| {
                 AppError::InternalServerError(format!("Failed to find commit: {}", e))
             })?;
+            let matches = Self::commit_matches(&commit, filter)
+                && match &path {
+                    Some(path) => history::touches_path(&commit, path)?,
+                    None => true,
+                };
+            // 本页已满时继续找下一个匹配的提交，找到后放回队列作为下一页的第一个
+            if matches && commits.len() >= limit {
+                queue.restore(pending);
+                has_more = true;
+                break;
+            }
 
-            commits.push(CommitInfo {
-                id: commit.id().to_string(),
-                author: format!(
-                    "{} <{}>",
-                    commit.author().name().unwrap_or(""),
-                    commit.author().email().unwrap_or("")
-                ),
-                message: commit.message().unwrap_or("").to_string(),
-                time: commit.time().seconds(),
-            });
+            for parent in commit.parents() {
+                queue.push(&parent);
+            }
+            if !matches {
+                continue;
+            }
+            matched += 1;
+            if matched > skip_count {
+                commits.push(Self::to_commit_info(&commit));
+            }
+        }
+
+        let next_cursor = match has_more {
+            true => Some(history::encode_cursor(queue)?),
+            false => None,
+        };
+
+        Ok(CommitHistoryPage {
+            commits,
+            next_cursor,
+        })
+    }
+
+    // 路径以外的过滤条件，只读取提交对象本身
+    fn commit_matches(commit: &git2::Commit, filter: &CommitFilter) -> bool {
+        let is_merge = commit.parent_count() > 1;
+        match filter.merges {
+            MergeFilter::Show => {}
+            MergeFilter::Hide if is_merge => return false,
+            MergeFilter::Only if !is_merge => return false,
+            _ => {}
+        }
+
+        let time = commit.time().seconds();
+        if filter.since.is_some_and(|since| time < since)
+            || filter.until.is_some_and(|until| time > until)
+        {
+            return false;
+        }
+
+        let signature_matches = |signature: git2::Signature, needle: &str| {
+            let needle = needle.to_lowercase();
+            [signature.name(), signature.email()]
+                .into_iter()
+                .flatten()
+                .any(|s| s.to_lowercase().contains(&needle))
+        };
+        if filter
+            .author
+            .as_deref()
+            .is_some_and(|author| !signature_matches(commit.author(), author))
+            || filter
+                .committer
+                .as_deref()
+                .is_some_and(|committer| !signature_matches(commit.committer(), committer))
+        {
+            return false;
         }
 
-        Ok(commits)
+        filter.message.as_ref().is_none_or(|message| {
+            message.is_match(&String::from_utf8_lossy(commit.message_bytes()))
+        })
     }
 
     // pub fn get_repo_status(&self, repo_name: &str) -> Result<Vec<String>, AppError> {
@@ -437,26 +615,13 @@ impl GitManager {
         user_id: &str,
         repo_name: &str,
         directory_path: Option<&str>,
-        branch: Option<&str>,
+        rev: Option<&str>, // 分支、标签、提交 ID 或任意 revspec，默认 HEAD
     ) -> Result<Vec<GitFileEntry>, AppError> {
         let repo_path = self.get_user_repo_path(user_id, repo_name);
         let repo = self.open_repo(&repo_path)?;
 
-        // 获取指定分支或默认分支的引用
-        let reference = match branch {
-            Some(branch_name) => repo
-                .find_branch(branch_name, git2::BranchType::Local)
-                .map_err(|_| AppError::NotFound(format!("Branch not found: {}", branch_name)))?
-                .into_reference(),
-            None => repo
-                .head()
-                .map_err(|e| AppError::InternalServerError(format!("Failed to get HEAD: {}", e)))?,
-        };
-
-        // 获取引用指向的提交
-        let commit = reference
-            .peel_to_commit()
-            .map_err(|e| AppError::InternalServerError(format!("Failed to get commit: {}", e)))?;
+        // 获取指定版本的提交
+        let commit = self.resolve_commit_or_head(&repo, rev)?;
 
         // 获取提交的树
         let tree = commit
@@ -464,277 +629,47 @@ impl GitManager {
             .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
 
         // 如果指定了目录路径，获取该目录的树
-        // let dir_tree = if let Some(path) = directory_path {
-        //     if path.is_empty() {
-        //         tree
-        //     } else {
-        //         let entry = tree
-        //             .get_path(Path::new(path))
-        //             .map_err(|_| AppError::NotFound(format!("Directory not found: {}", path)))?;
-
-        //         entry
-        //             .to_object(&repo)
-        //             .map_err(|e| {
-        //                 AppError::InternalServerError(format!("Failed to get object: {}", e))
-        //             })?
-        //             .as_tree()
-        //             .ok_or(AppError::BadRequest("Path is not a directory".to_string()))?
-        //             .clone()
-        //     }
-        // } else {
-        //     tree
-        // };
-        self.get_tree_entries(&repo, &tree, directory_path)
-        // let mut files = Vec::new();
-
-        // // 遍历树中的条目
-        // for entry in dir_tree.iter() {
-        //     let entry_name = entry.name().unwrap_or_default().to_string();
-        //     let entry_path = match directory_path {
-        //         Some(path) if !path.is_empty() => format!("{}/{}", path, entry_name),
-        //         _ => entry_name.clone(),
-        //     };
-
-        //     let object = entry.to_object(&repo).map_err(|e| {
-        //         AppError::InternalServerError(format!("Failed to get object: {}", e))
-        //     })?;
-
-        //     let is_dir = object.as_tree().is_some();
-        //     let size = if !is_dir {
-        //         object.as_blob().map(|b| b.content().len() as u64)
-        //     } else {
-        //         None
-        //     };
-
-        //     files.push(GitFileEntry {
-        //         name: entry_name,
-        //         path: entry_path,
-        //         is_dir,
-        //         size,
-        //     });
-        // }
-
-        // Ok(files)
+        let directory_path = tree::normalize_tree_path(directory_path.unwrap_or(""));
+        let dir_tree = self.find_subtree(&repo, &tree, &directory_path)?;
+
+        let odb = repo
+            .odb()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to open odb: {}", e)))?;
+        self.get_tree_entries(&repo, &odb, &dir_tree, &directory_path)
     }
 
-    fn get_tree_entries(
+    pub(super) fn get_tree_entries(
         &self,
         repo: &Repository,
+        odb: &git2::Odb,
         tree: &git2::Tree,
-        prefix: Option<&str>,
+        prefix: &str,
     ) -> Result<Vec<GitFileEntry>, AppError> {
         let mut files = Vec::new();
 
         // 遍历当前树中的条目
         for entry in tree.iter() {
-            let entry_name = entry.name().unwrap_or_default().to_string();
-            let entry_path = match prefix {
-                Some(path) if !path.is_empty() => format!("{}/{}", path, entry_name),
-                _ => entry_name.clone(),
-            };
-
-            let object = entry.to_object(repo).map_err(|e| {
-                AppError::InternalServerError(format!("Failed to get object: {}", e))
-            })?;
-
-            let is_dir = object.as_tree().is_some();
-            let size = if !is_dir {
-                object.as_blob().map(|b| b.content().len() as u64)
-            } else {
-                None
-            };
+            let mut file = tree::tree_entry_info(odb, &entry, prefix);
 
-            // 添加当前条目
-            // files.push(GitFileEntry {
-            //     name: entry_name,
-            //     path: entry_path.clone(),
-            //     is_dir,
-            //     size,
-            // });
-
-            // 如果是目录，递归获取其内容
             // 为目录递归获取子项
-            let children = if is_dir {
-                if let Some(subtree) = object.as_tree() {
-                    self.get_tree_entries(repo, &subtree, Some(&entry_path))?
-                } else {
-                    Vec::new()
-                }
-            } else {
-                Vec::new()
-            };
-            files.push(GitFileEntry {
-                name: entry_name,
-                path: entry_path,
-                is_dir,
-                size,
-                children,
-            });
+            if file.is_dir {
+                let subtree = repo.find_tree(entry.id()).map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to get tree: {}", e))
+                })?;
+                file.children = self.get_tree_entries(repo, odb, &subtree, &file.path)?;
+            }
+            files.push(file);
         }
 
         Ok(files)
     }
 
-    pub fn get_file_content(
-        &self,
-        user_id: &str,
-        repo_name: &str,
-        file_path: &str,
-        branch: Option<&str>,
-    ) -> Result<String, AppError> {
-        let repo_path = self.get_user_repo_path(user_id, repo_name);
-        let repo = self.open_repo(&repo_path)?;
-
-        // 获取指定分支或默认分支的引用
-        let reference = match branch {
-            Some(branch_name) => repo
-                .find_branch(branch_name, git2::BranchType::Local)
-                .map_err(|e| AppError::NotFound(format!("Branch not found: {}", e)))?
-                .into_reference(),
-            None => repo
-                .head()
-                .map_err(|e| AppError::InternalServerError(format!("Failed to get HEAD: {}", e)))?,
-        };
-
-        // 获取引用指向的提交
-        let commit = reference
-            .peel_to_commit()
-            .map_err(|e| AppError::InternalServerError(format!("Failed to get commit: {}", e)))?;
-
-        // 获取提交的树
-        let tree = commit
-            .tree()
-            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
-
-        // 在树中查找文件
-        let entry = tree
-            .get_path(Path::new(file_path))
-            .map_err(|_| AppError::NotFound(format!("File not found: {}", file_path)))?;
-
-        // 获取文件对象
-        let object = entry
-            .to_object(&repo)
-            .map_err(|e| AppError::InternalServerError(format!("Failed to get object: {}", e)))?;
-
-        // 获取文件 blob
-        let blob = object.as_blob().ok_or(AppError::InternalServerError(
-            "Object is not a blob".to_string(),
-        ))?;
-
-        // 获取文件内容
-        let content = std::str::from_utf8(blob.content())
-            .map_err(|_| {
-                AppError::InternalServerError("File content is not valid UTF-8".to_string())
-            })?
-            .to_string();
-
-        Ok(content)
-    }
-
-    pub fn update_file(
-        &self,
-        user_id: &str,
-        repo_name: &str,
-        file_path: &str,
-        content: &str,
-        commit_message: &str,
-        user_email: &str,
-    ) -> Result<String, AppError> {
-        let repo_path = self.get_user_repo_path(user_id, repo_name);
-        let repo = self.open_repo(&repo_path)?;
-        let full_file_path = repo_path.join(file_path);
-
-        // 确保目录存在
-        if let Some(parent) = full_file_path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| {
-                AppError::InternalServerError(format!("Failed to create directory: {}", e))
-            })?;
-        }
-
-        // 写入文件内容
-        std::fs::write(&full_file_path, content)
-            .map_err(|e| AppError::InternalServerError(format!("Failed to write file: {}", e)))?;
-
-        // 获取索引
-        let mut index = repo
-            .index()
-            .map_err(|e| AppError::InternalServerError(format!("Failed to get index: {}", e)))?;
-
-        // 添加文件到索引
-        index.add_path(Path::new(file_path)).map_err(|e| {
-            AppError::InternalServerError(format!("Failed to add file to index: {}", e))
-        })?;
-
-        // 写入索引
-        let oid = index
-            .write_tree()
-            .map_err(|e| AppError::InternalServerError(format!("Failed to write index: {}", e)))?;
-
-        // 写入索引文件
-        index.write().map_err(|e| {
-            AppError::InternalServerError(format!("Failed to write index file: {}", e))
-        })?;
-
-        // 创建签名
-        let signature = Signature::now(user_id, user_email).map_err(|e| {
-            AppError::InternalServerError(format!("Failed to create signature: {}", e))
-        })?;
-
-        // 查找树
-        let tree = repo
-            .find_tree(oid)
-            .map_err(|e| AppError::InternalServerError(format!("Failed to find tree: {}", e)))?;
-
-        // 获取父提交
-        let parent_commit = match repo.head() {
-            Ok(head) => match head.peel_to_commit() {
-                Ok(commit) => Some(commit),
-                Err(e) => {
-                    return Err(AppError::InternalServerError(format!(
-                        "Failed to peel HEAD: {}",
-                        e
-                    )));
-                }
-            },
-            Err(e)
-                if e.code() == git2::ErrorCode::UnbornBranch
-                    || e.code() == git2::ErrorCode::NotFound =>
-            {
-                None
-            }
-            Err(e) => {
-                return Err(AppError::InternalServerError(format!(
-                    "Failed to get HEAD: {}",
-                    e
-                )));
-            }
-        };
-
-        // 准备父提交引用
-        let parents_vec: Vec<&git2::Commit> =
-            parent_commit.as_ref().map_or(Vec::new(), |c| vec![c]);
-
-        // 创建提交
-        let commit_id = repo
-            .commit(
-                Some("HEAD"),
-                &signature,
-                &signature,
-                commit_message,
-                &tree,
-                &parents_vec,
-            )
-            .map_err(|e| AppError::InternalServerError(format!("Failed to commit: {}", e)))?;
-
-        println!("commit success {}", commit_id);
-        Ok(commit_id.to_string())
-    }
-
     pub async fn get_commit_detail(
         &self,
         user_id: &str,
         repo_name: &str,
         commit_id: &str,
+        settings: &DiffSettings,
     ) -> Result<CommitDetail, AppError> {
         let repo_path = self.get_user_repo_path(user_id, repo_name);
         let repo = self.open_repo(&repo_path)?;
@@ -749,22 +684,14 @@ impl GitManager {
             .map_err(|e| AppError::NotFound(format!("Commit not found: {}", e)))?;
 
         // 构建基本提交信息
-        let commit_info: CommitInfo = CommitInfo {
-            id: commit.id().to_string(),
-            author: format!(
-                "{} <{}>",
-                commit.author().name().unwrap_or(""),
-                commit.author().email().unwrap_or("")
-            ),
-            message: commit.message().unwrap_or("").to_string(),
-            time: commit.time().seconds(),
-        };
+        let commit_info = Self::to_commit_info(&commit);
 
-        // 获取父提交
-        let parent_commit = if commit.parent_count() > 0 {
-            commit.parent(0).ok()
-        } else {
-            None
+        // 获取父提交的树，第一次提交与空树比较
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree().map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get parent tree: {}", e))
+            })?),
+            Err(_) => None,
         };
 
         // 获取提交树
@@ -772,142 +699,20 @@ impl GitManager {
             AppError::InternalServerError(format!("Failed to get commit tree: {}", e))
         })?;
 
-        let mut file_changes = Vec::new();
-
-        // 如果有父提交，比较与父提交的差异
-        if let Some(parent) = parent_commit {
-            let parent_tree = parent.tree().map_err(|e| {
-                AppError::InternalServerError(format!("Failed to get parent tree: {}", e))
-            })?;
-
-            let diff = repo
-                .diff_tree_to_tree(Some(&parent_tree), Some(&commit_tree), None)
-                .map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to compute diff: {}", e))
-                })?;
-
-            // 处理每个修改的文件
-            self.process_diff_into_changes(&diff, &mut file_changes)?;
-        } else {
-            // 第一次提交，与空树比较
-            let empty_tree = {
-                let treebuilder = repo.treebuilder(None).map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to create treebuilder: {}", e))
-                })?;
-                let oid = treebuilder.write().map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to create empty tree: {}", e))
-                })?;
-                repo.find_tree(oid).map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to find empty tree: {}", e))
-                })?
-            };
+        let diff = self.diff_trees(&repo, parent_tree.as_ref(), Some(&commit_tree), settings)?;
 
-            let diff = repo
-                .diff_tree_to_tree(Some(&empty_tree), Some(&commit_tree), None)
-                .map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to compute diff: {}", e))
-                })?;
-
-            // 处理每个修改的文件
-            // self.process_diff_into_changes(&repo, &diff, &mut file_changes)?;
-            self.process_diff_into_changes(&diff, &mut file_changes)?;
-        }
+        // 处理每个修改的文件
+        let mut file_changes = Vec::new();
+        let stats =
+            self.process_diff_into_changes(&repo, &diff, &mut file_changes, &settings.limits)?;
 
         Ok(CommitDetail {
             commit_info,
+            stats,
             file_changes,
         })
     }
 
-    fn process_diff_into_changes(
-        &self,
-        // _repo: &Repository,
-        diff: &git2::Diff,
-        file_changes: &mut Vec<CommitFileChange>,
-    ) -> Result<(), AppError> {
-        // 首先收集所有修改的文件信息
-        diff.foreach(
-            &mut |delta, _| {
-                let status = match delta.status() {
-                    git2::Delta::Added => "added".to_string(),
-                    git2::Delta::Deleted => "deleted".to_string(),
-                    git2::Delta::Modified => "modified".to_string(),
-                    git2::Delta::Renamed => "renamed".to_string(),
-                    git2::Delta::Copied => "copied".to_string(),
-                    _ => "changed".to_string(),
-                };
-
-                let old_path = delta
-                    .old_file()
-                    .path()
-                    .map(|p| p.to_string_lossy().into_owned())
-                    .unwrap_or_default();
-                let new_path = delta
-                    .new_file()
-                    .path()
-                    .map(|p| p.to_string_lossy().into_owned())
-                    .unwrap_or_default();
-
-                let path = if delta.status() == git2::Delta::Deleted {
-                    old_path
-                } else {
-                    new_path
-                };
-
-                file_changes.push(CommitFileChange {
-                    path,
-                    status,
-                    diff: None, // 将在后续步骤填充
-                });
-
-                true
-            },
-            None,
-            None,
-            None,
-        )
-        .map_err(|e| AppError::InternalServerError(format!("Failed to process diff: {}", e)))?;
-
-        // 然后为每个文件获取详细的差异
-        diff.print(git2::DiffFormat::Patch, |delta, hunk, line| {
-            // 根据delta找到对应的文件变更记录
-            let path = delta
-                .new_file()
-                .path()
-                .or_else(|| delta.old_file().path())
-                .map(|p| p.to_string_lossy().into_owned())
-                .unwrap_or_default();
-
-            // 查找对应的文件变更记录
-            if let Some(pos) = file_changes.iter().position(|change| change.path == path) {
-                let file_change = &mut file_changes[pos];
-
-                // if not init:
-                if file_change.diff.is_none() {
-                    file_change.diff = Some(String::new());
-                }
-
-                // 添加行内容到差异文本
-                if let Some(diff_text) = &mut file_change.diff {
-                    let prefix = match line.origin() {
-                        '+' => "+",
-                        '-' => "-",
-                        'H' => "",
-                        _ => " ",
-                    };
-
-                    let content = std::str::from_utf8(line.content()).unwrap_or("[Invalid UTF-8]");
-                    diff_text.push_str(&format!("{}{}", prefix, content));
-                }
-            }
-
-            true
-        })
-        .map_err(|e| AppError::InternalServerError(format!("Failed to print diff: {}", e)))?;
-
-        Ok(())
-    }
-
     pub async fn del_repo(&self, user_id: &str, repo_name: &str) -> Result<(), AppError> {
         let repo_path = self.get_user_repo_path(user_id, repo_name);
         // 删除仓库目录
@@ -959,40 +764,15 @@ impl GitManager {
         Ok(())
     }
 
-    pub async fn get_repo_branchs(
-        &self,
-        user_id: &str,
-        repo_name: &str,
-    ) -> Result<Vec<String>, AppError> {
-        let repo_path = self.get_user_repo_path(user_id, repo_name);
-        let repo = self.open_repo(&repo_path)?;
-
-        // 获取所有本地分支
-        let mut branches = Vec::new();
-        for branch in repo
-            .branches(None)
-            .map_err(|e| AppError::InternalServerError(format!("Failed to get branches: {}", e)))?
-        {
-            let (branch, t_) = branch.map_err(|e| {
-                AppError::InternalServerError(format!("Failed to get branch: {}", e))
-            })?;
-            let branch_name = branch.name().map_err(|e| {
-                AppError::InternalServerError(format!("Failed to get branch name: {}", e))
-            })?;
-            if let Some(name) = branch_name {
-                branches.push(name.to_string());
-            }
-        }
-
-        Ok(branches)
-    }
-
     pub async fn pull_repo(
         &self,
         user_id: &str,
         repo_name: &str,
         branch: Option<&str>,
-    ) -> Result<(), AppError> {
+        strategy: PullStrategy,
+        committer: &GitConfig,
+        credential: Option<&RemoteCredential>,
+    ) -> Result<PullResult, AppError> {
         let repo_path = self.get_user_repo_path(user_id, repo_name);
         let repo = self.open_repo(&repo_path)?;
 
@@ -1018,8 +798,10 @@ impl GitManager {
             .find_remote("origin")
             .map_err(|e| AppError::InternalServerError(format!("Failed to find remote: {}", e)))?;
 
+        let mut fetch_opts = FetchOptions::new();
+        fetch_opts.remote_callbacks(remote_callbacks(credential));
         remote
-            .fetch(&[&branch_name], None, None)
+            .fetch(&[&branch_name], Some(&mut fetch_opts), None)
             .map_err(|e| AppError::InternalServerError(format!("Failed to fetch: {}", e)))?;
 
         // 构造远程分支引用名称 (例如 "refs/remotes/origin/main")
@@ -1029,28 +811,508 @@ impl GitManager {
         let remote_ref = repo.find_reference(&remote_ref_name).map_err(|e| {
             AppError::InternalServerError(format!("Failed to find remote reference: {}", e))
         })?;
+        let remote_commit = repo
+            .reference_to_annotated_commit(&remote_ref)
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to resolve remote commit: {}", e))
+            })?;
+        let target_oid = remote_commit.id();
+
+        let local_ref_name = format!("refs/heads/{}", branch_name);
+        let is_head = repo
+            .head()
+            .ok()
+            .is_some_and(|head| head.name() == Some(local_ref_name.as_str()));
+
+        let local_ref = match repo.find_reference(&local_ref_name) {
+            Ok(reference) => reference,
+            Err(e) if e.code() == git2::ErrorCode::NotFound => {
+                // 本地还没有该分支，直接以远程分支为起点创建
+                self.move_branch(&repo, &local_ref_name, is_head, target_oid, "pull: create")?;
+                return Ok(PullResult {
+                    branch: branch_name,
+                    strategy,
+                    outcome: PullOutcome::FastForward,
+                    head: target_oid.to_string(),
+                });
+            }
+            Err(e) => {
+                return Err(AppError::InternalServerError(format!(
+                    "Failed to find local branch: {}",
+                    e
+                )));
+            }
+        };
 
-        // 获取远程分支的目标提交ID
-        let target_oid = remote_ref
-            .target()
-            .ok_or_else(|| AppError::InternalServerError("Failed to get target OID".to_string()))?;
-        repo.reference(
-            &format!("refs/heads/{}", branch_name),
-            target_oid,
-            true, // force
-            &format!("Fast-forward pull of '{}' to {}", branch_name, target_oid),
-        )
-        .map_err(|e| AppError::InternalServerError(format!("Failed to update reference: {}", e)))?;
-
-        // 更新工作目录
-        let mut checkout_opts = git2::build::CheckoutBuilder::new();
-        checkout_opts.force();
-        repo.checkout_head(Some(&mut checkout_opts)).map_err(|e| {
-            AppError::InternalServerError(format!("Failed to checkout HEAD: {}", e))
-        })?;
+        let (analysis, _) = repo
+            .merge_analysis_for_ref(&local_ref, &[&remote_commit])
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to analyze merge: {}", e))
+            })?;
+
+        let (outcome, head) = if analysis.is_up_to_date() {
+            let head = local_ref.target().ok_or_else(|| {
+                AppError::InternalServerError("Failed to get target OID".to_string())
+            })?;
+            (PullOutcome::UpToDate, head)
+        } else if analysis.is_fast_forward() {
+            self.move_branch(
+                &repo,
+                &local_ref_name,
+                is_head,
+                target_oid,
+                &format!("pull: Fast-forward '{}' to {}", branch_name, target_oid),
+            )?;
+            (PullOutcome::FastForward, target_oid)
+        } else if analysis.is_normal() {
+            let signature = Signature::now(&committer.name, &committer.email).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to create signature: {}", e))
+            })?;
+            match strategy {
+                PullStrategy::Merge => {
+                    let head = self.pull_merge(
+                        &repo,
+                        &local_ref,
+                        &remote_commit,
+                        &branch_name,
+                        is_head,
+                        &signature,
+                    )?;
+                    (PullOutcome::Merged, head)
+                }
+                PullStrategy::Rebase => {
+                    let head = self.pull_rebase(
+                        &repo,
+                        &local_ref,
+                        &remote_commit,
+                        &branch_name,
+                        is_head,
+                        &signature,
+                    )?;
+                    (PullOutcome::Rebased, head)
+                }
+            }
+        } else {
+            return Err(AppError::BadRequest(format!(
+                "Branch {} cannot be merged with origin/{}",
+                branch_name, branch_name
+            )));
+        };
 
         info!("Repository {} pulled successfully", repo_name);
 
+        Ok(PullResult {
+            branch: branch_name,
+            strategy,
+            outcome,
+            head: head.to_string(),
+        })
+    }
+
+    // 在内存中合并远程分支并生成合并提交，不会留下 MERGE_HEAD 等中间状态
+    fn pull_merge(
+        &self,
+        repo: &Repository,
+        local_ref: &git2::Reference,
+        remote_commit: &git2::AnnotatedCommit,
+        branch_name: &str,
+        is_head: bool,
+        signature: &Signature,
+    ) -> Result<git2::Oid, AppError> {
+        let local = local_ref.peel_to_commit().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to get local commit: {}", e))
+        })?;
+        let remote = repo.find_commit(remote_commit.id()).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to get remote commit: {}", e))
+        })?;
+
+        let mut index = repo
+            .merge_commits(&local, &remote, None)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to merge: {}", e)))?;
+
+        if index.has_conflicts() {
+            return Err(AppError::Conflict(
+                "Merge conflict".to_string(),
+                serde_json::json!({
+                    "branch": branch_name,
+                    "strategy": PullStrategy::Merge,
+                    "conflicts": self.collect_conflicts(&index)?,
+                }),
+            ));
+        }
+
+        let tree_oid = index
+            .write_tree_to(repo)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write tree: {}", e)))?;
+        let tree = repo
+            .find_tree(tree_oid)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to find tree: {}", e)))?;
+
+        let message = format!(
+            "Merge remote-tracking branch 'origin/{}' into {}",
+            branch_name, branch_name
+        );
+        let merge_oid = repo
+            .commit(
+                None,
+                signature,
+                signature,
+                &message,
+                &tree,
+                &[&local, &remote],
+            )
+            .map_err(|e| AppError::InternalServerError(format!("Failed to commit: {}", e)))?;
+
+        self.move_branch(
+            repo,
+            local_ref.name().unwrap_or_default(),
+            is_head,
+            merge_oid,
+            &format!("pull: {}", message),
+        )?;
+
+        Ok(merge_oid)
+    }
+
+    // 在内存中把本地提交变基到远程分支之上，出现冲突时放弃整个变基
+    fn pull_rebase(
+        &self,
+        repo: &Repository,
+        local_ref: &git2::Reference,
+        remote_commit: &git2::AnnotatedCommit,
+        branch_name: &str,
+        is_head: bool,
+        signature: &Signature,
+    ) -> Result<git2::Oid, AppError> {
+        let local = repo.reference_to_annotated_commit(local_ref).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to get local commit: {}", e))
+        })?;
+
+        let mut rebase_opts = git2::RebaseOptions::new();
+        rebase_opts.inmemory(true);
+        let mut rebase = repo
+            .rebase(
+                Some(&local),
+                Some(remote_commit),
+                None,
+                Some(&mut rebase_opts),
+            )
+            .map_err(|e| AppError::InternalServerError(format!("Failed to start rebase: {}", e)))?;
+
+        let mut new_head = remote_commit.id();
+        while let Some(operation) = rebase.next() {
+            let operation = operation.map_err(|e| {
+                AppError::InternalServerError(format!("Failed to apply rebase step: {}", e))
+            })?;
+
+            let index = rebase.inmemory_index().map_err(|e| {
+                AppError::InternalServerError(format!("Failed to get rebase index: {}", e))
+            })?;
+            if index.has_conflicts() {
+                let conflicts = self.collect_conflicts(&index)?;
+                let _ = rebase.abort();
+                return Err(AppError::Conflict(
+                    "Rebase conflict".to_string(),
+                    serde_json::json!({
+                        "branch": branch_name,
+                        "strategy": PullStrategy::Rebase,
+                        "commit": operation.id().to_string(),
+                        "conflicts": conflicts,
+                    }),
+                ));
+            }
+
+            match rebase.commit(None, signature, None) {
+                Ok(oid) => new_head = oid,
+                // 变基后没有改动的提交直接跳过
+                Err(e) if e.code() == git2::ErrorCode::Applied => {}
+                Err(e) => {
+                    let _ = rebase.abort();
+                    return Err(AppError::InternalServerError(format!(
+                        "Failed to commit rebase step: {}",
+                        e
+                    )));
+                }
+            }
+        }
+        rebase.finish(Some(signature)).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to finish rebase: {}", e))
+        })?;
+
+        self.move_branch(
+            repo,
+            local_ref.name().unwrap_or_default(),
+            is_head,
+            new_head,
+            &format!("pull --rebase: {} onto {}", branch_name, remote_commit.id()),
+        )?;
+
+        Ok(new_head)
+    }
+
+    // 移动分支到新的提交，若是当前分支则先安全地更新工作区
+    fn move_branch(
+        &self,
+        repo: &Repository,
+        ref_name: &str,
+        is_head: bool,
+        target: git2::Oid,
+        log_message: &str,
+    ) -> Result<(), AppError> {
+        if is_head {
+            self.checkout_tree_safely(repo, target)?;
+        }
+
+        repo.reference(ref_name, target, true, log_message)
+            .map_err(|e| {
+                AppError::InternalServerError(format!("Failed to update reference: {}", e))
+            })?;
+
         Ok(())
     }
+
+    // 以 safe 模式检出提交的树，本地未提交的修改会被保留
+    // 如果修改会被覆盖则拒绝检出，并返回受影响的文件
+    fn checkout_tree_safely(&self, repo: &Repository, target: git2::Oid) -> Result<(), AppError> {
+        let object = repo
+            .find_object(target, None)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to find object: {}", e)))?;
+
+        let mut dirty_paths = Vec::new();
+        let result = {
+            let mut checkout_opts = git2::build::CheckoutBuilder::new();
+            checkout_opts
+                .safe()
+                .notify_on(git2::CheckoutNotificationType::CONFLICT)
+                .notify(|_, path, _, _, _| {
+                    if let Some(path) = path {
+                        dirty_paths.push(path.to_string_lossy().into_owned());
+                    }
+                    true
+                });
+            repo.checkout_tree(&object, Some(&mut checkout_opts))
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if e.code() == git2::ErrorCode::Conflict || !dirty_paths.is_empty() => {
+                Err(AppError::Conflict(
+                    "Local changes would be overwritten by checkout".to_string(),
+                    serde_json::json!({ "paths": dirty_paths }),
+                ))
+            }
+            Err(e) => Err(AppError::InternalServerError(format!(
+                "Failed to checkout: {}",
+                e
+            ))),
+        }
+    }
+
+    // 收集索引中的冲突文件
+    fn collect_conflicts(&self, index: &git2::Index) -> Result<Vec<ConflictEntry>, AppError> {
+        let conflicts = index.conflicts().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to read conflicts: {}", e))
+        })?;
+
+        let mut entries = Vec::new();
+        for conflict in conflicts {
+            let conflict = conflict.map_err(|e| {
+                AppError::InternalServerError(format!("Failed to read conflict: {}", e))
+            })?;
+
+            let path = [&conflict.our, &conflict.their, &conflict.ancestor]
+                .into_iter()
+                .flatten()
+                .next()
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                .unwrap_or_default();
+
+            entries.push(ConflictEntry {
+                path,
+                ancestor_id: conflict.ancestor.as_ref().map(|e| e.id.to_string()),
+                our_id: conflict.our.as_ref().map(|e| e.id.to_string()),
+                their_id: conflict.their.as_ref().map(|e| e.id.to_string()),
+            });
+        }
+
+        Ok(entries)
+    }
+
+    pub fn get_remote_url(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        remote_name: &str,
+    ) -> Result<String, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let remote = repo
+            .find_remote(remote_name)
+            .map_err(|e| AppError::NotFound(format!("Remote not found: {}", e)))?;
+        remote
+            .url()
+            .map(|url| url.to_string())
+            .ok_or_else(|| AppError::InternalServerError("Remote URL is not UTF-8".to_string()))
+    }
+
+    // 将本地分支推送到远端，推送进度通过 websocket 通知前端
+    pub async fn push_repo(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        remote_name: Option<&str>,
+        branch: Option<&str>,
+        credential: Option<RemoteCredential>,
+        ws_manager: &WebSocketManager,
+    ) -> Result<PushResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let remote_name = remote_name.unwrap_or("origin").to_string();
+        let branch = branch.map(|b| b.to_string());
+
+        // 推送回调在阻塞线程中执行，通过通道把进度转发给 websocket
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let ws_manager_cloned = ws_manager.clone();
+        let user_id_cloned = user_id.to_string();
+        let repo_name_cloned = repo_name.to_string();
+        let forward = tokio::spawn(async move {
+            while let Some(event) = progress_rx.recv().await {
+                ws_manager_cloned
+                    .send_repo_event(&user_id_cloned, &repo_name_cloned, &event)
+                    .await;
+            }
+        });
+
+        let manager = self.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            manager.push_blocking(
+                &repo_path,
+                &remote_name,
+                branch.as_deref(),
+                credential.as_ref(),
+                progress_tx,
+            )
+        })
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Push task failed: {}", e)))?;
+
+        // 发送端已全部释放，等待剩余进度消息转发完成
+        let _ = forward.await;
+
+        let status = if result.is_ok() {
+            "PUSH_COMPLETED"
+        } else {
+            "PUSH_FAILED"
+        };
+        ws_manager.send_repo_event(user_id, repo_name, status).await;
+
+        result
+    }
+
+    fn push_blocking(
+        &self,
+        repo_path: &Path,
+        remote_name: &str,
+        branch: Option<&str>,
+        credential: Option<&RemoteCredential>,
+        progress_tx: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> Result<PushResult, AppError> {
+        let repo = self.open_repo(repo_path)?;
+
+        let branch_name = match branch {
+            Some(name) => name.to_string(),
+            None => {
+                let head = repo.head().map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to get HEAD: {}", e))
+                })?;
+                if !head.is_branch() {
+                    return Err(AppError::BadRequest("HEAD is not a branch".to_string()));
+                }
+                head.shorthand()
+                    .ok_or(AppError::InternalServerError(
+                        "HEAD is not a branch".to_string(),
+                    ))?
+                    .to_string()
+            }
+        };
+
+        // 确认本地分支存在
+        repo.find_branch(&branch_name, git2::BranchType::Local)
+            .map_err(|_| AppError::NotFound(format!("Branch not found: {}", branch_name)))?;
+
+        let mut remote = repo
+            .find_remote(remote_name)
+            .map_err(|e| AppError::NotFound(format!("Remote not found: {}", e)))?;
+
+        let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
+        let mut refs: Vec<PushRefStatus> = Vec::new();
+
+        let push_result = {
+            let transfer_tx = progress_tx.clone();
+            let ref_tx = progress_tx.clone();
+            let mut callbacks = remote_callbacks(credential);
+            callbacks.push_transfer_progress(move |current, total, bytes| {
+                let _ = transfer_tx.send(format!("PUSH_PROGRESS:{}/{}:{}", current, total, bytes));
+            });
+            callbacks.push_update_reference(|reference, status| {
+                let event = match status {
+                    Some(msg) => format!("PUSH_REF:{}:REJECTED:{}", reference, msg),
+                    None => format!("PUSH_REF:{}:OK", reference),
+                };
+                let _ = ref_tx.send(event);
+                refs.push(PushRefStatus {
+                    reference: reference.to_string(),
+                    success: status.is_none(),
+                    message: status.map(|m| m.to_string()),
+                });
+                Ok(())
+            });
+
+            let mut push_opts = git2::PushOptions::new();
+            push_opts.remote_callbacks(callbacks);
+            remote.push(&[&refspec], Some(&mut push_opts))
+        };
+        drop(progress_tx);
+
+        if let Err(e) = push_result {
+            if e.code() == git2::ErrorCode::NotFastForward {
+                return Err(AppError::Conflict(
+                    "Push rejected: non-fast-forward update".to_string(),
+                    serde_json::json!({
+                        "remote": remote_name,
+                        "branch": branch_name,
+                        "refs": [{
+                            "reference": format!("refs/heads/{}", branch_name),
+                            "success": false,
+                            "message": e.message(),
+                        }],
+                    }),
+                ));
+            }
+            return Err(AppError::InternalServerError(format!(
+                "Failed to push: {}",
+                e
+            )));
+        }
+
+        // 远端拒绝的引用更新（例如非快进）不会让 push 返回错误，需要单独检查
+        if refs.iter().any(|r| !r.success) {
+            return Err(AppError::Conflict(
+                "Push rejected by remote".to_string(),
+                serde_json::json!({
+                    "remote": remote_name,
+                    "branch": branch_name,
+                    "refs": refs,
+                }),
+            ));
+        }
+
+        info!("Branch {} pushed to {}", branch_name, remote_name);
+
+        Ok(PushResult {
+            remote: remote_name.to_string(),
+            branch: branch_name,
+            refs,
+        })
+    }
 }
@@ -0,0 +1,90 @@
+use git2::{ErrorCode, Oid, Repository, ResetType};
+
+use super::GitManager;
+use super::structs::{HistoryOperation, HistoryOperationResult, ResetMode};
+use crate::shared::error::AppError;
+
+// 硬重置前旧的分支指向保存在 refs/backup/<分支>/<时间戳> 下，同一秒内多次重置时加 -1、-2 等后缀
+const BACKUP_REF_PREFIX: &str = "refs/backup";
+
+impl GitManager {
+    // 把分支重置到指定提交，当前分支按模式同时更新索引和工作区
+    // hard 模式会丢弃工作区中未提交的修改，重置前把旧的分支指向保存到备份引用，之后可以重置回去
+    pub fn reset_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        branch: Option<&str>,
+        mode: ResetMode,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let (branch_name, ref_name, is_head) = self.target_branch(&repo, branch)?;
+        let previous = self.resolve_commit(&repo, &ref_name)?;
+        let target = self.resolve_commit(&repo, commit_id)?;
+        let log_message = format!("reset: moving to {}", target.id());
+
+        let backup_ref = match mode {
+            ResetMode::Hard => Some(Self::create_backup_ref(&repo, &branch_name, previous.id())?),
+            _ => None,
+        };
+
+        if is_head {
+            let reset_type = match mode {
+                ResetMode::Soft => ResetType::Soft,
+                ResetMode::Mixed => ResetType::Mixed,
+                ResetMode::Hard => ResetType::Hard,
+            };
+            repo.reset(target.as_object(), reset_type, None)
+                .map_err(|e| AppError::InternalServerError(format!("Failed to reset: {}", e)))?;
+        } else {
+            // 其他分支没有索引和工作区，只移动分支
+            repo.reference(&ref_name, target.id(), true, &log_message)
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to update reference: {}", e))
+                })?;
+        }
+
+        Ok(HistoryOperationResult {
+            branch: branch_name,
+            operation: HistoryOperation::Reset,
+            previous_head: previous.id().to_string(),
+            head: target.id().to_string(),
+            backup_ref,
+        })
+    }
+
+    // 不覆盖已有的备份，名称冲突时依次尝试带后缀的名称
+    fn create_backup_ref(
+        repo: &Repository,
+        branch_name: &str,
+        target: Oid,
+    ) -> Result<String, AppError> {
+        let base = format!(
+            "{}/{}/{}",
+            BACKUP_REF_PREFIX,
+            branch_name,
+            chrono::Utc::now().timestamp()
+        );
+        let message = format!("reset: backup of {} before hard reset", branch_name);
+        let mut suffix = 0;
+        loop {
+            let backup_ref = match suffix {
+                0 => base.clone(),
+                n => format!("{}-{}", base, n),
+            };
+            match repo.reference(&backup_ref, target, false, &message) {
+                Ok(_) => return Ok(backup_ref),
+                Err(e) if e.code() == ErrorCode::Exists => suffix += 1,
+                Err(e) => {
+                    return Err(AppError::InternalServerError(format!(
+                        "Failed to create backup ref: {}",
+                        e
+                    )));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use git2::{Repository, Signature};
+
+use super::structs::{HistoryOperation, HistoryOperationResult};
+use super::{GitConfig, GitManager};
+use crate::shared::error::AppError;
+
+impl GitManager {
+    // 解析要操作的分支，未指定时使用 HEAD 所在的分支，返回分支名、引用名和是否为当前分支
+    pub(super) fn target_branch(
+        &self,
+        repo: &Repository,
+        branch: Option<&str>,
+    ) -> Result<(String, String, bool), AppError> {
+        let head = repo.head().ok();
+        let head_name = head
+            .as_ref()
+            .filter(|h| h.is_branch())
+            .and_then(|h| h.name())
+            .map(|n| n.to_string());
+
+        let ref_name = match branch.map(str::trim).filter(|b| !b.is_empty()) {
+            Some(branch) => format!("refs/heads/{}", branch),
+            None => head_name
+                .clone()
+                .ok_or_else(|| AppError::BadRequest("HEAD is not a branch".to_string()))?,
+        };
+        repo.find_reference(&ref_name).map_err(|_| {
+            AppError::NotFound(format!(
+                "Branch not found: {}",
+                ref_name.trim_start_matches("refs/heads/")
+            ))
+        })?;
+
+        let is_head = head_name.as_deref() == Some(ref_name.as_str());
+        let branch_name = ref_name.trim_start_matches("refs/heads/").to_string();
+        Ok((branch_name, ref_name, is_head))
+    }
+
+    // 生成撤销指定提交改动的新提交，合并提交需要通过 mainline 指定保留哪个父提交（从 1 开始）
+    pub fn revert_commit(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        branch: Option<&str>,
+        mainline: Option<u32>,
+        committer: &GitConfig,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+        self.apply_commit(
+            &repo,
+            commit_id,
+            branch,
+            mainline,
+            committer,
+            HistoryOperation::Revert,
+        )
+    }
+
+    // 把指定提交的改动应用到分支上，保留原作者
+    pub fn cherry_pick_commit(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        branch: Option<&str>,
+        mainline: Option<u32>,
+        committer: &GitConfig,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+        self.apply_commit(
+            &repo,
+            commit_id,
+            branch,
+            mainline,
+            committer,
+            HistoryOperation::CherryPick,
+        )
+    }
+
+    // revert 和 cherry-pick 都在内存中完成，出现冲突时分支和工作区保持不变
+    fn apply_commit(
+        &self,
+        repo: &Repository,
+        commit_id: &str,
+        branch: Option<&str>,
+        mainline: Option<u32>,
+        committer: &GitConfig,
+        operation: HistoryOperation,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let (branch_name, ref_name, is_head) = self.target_branch(repo, branch)?;
+        let ours = self.resolve_commit(repo, &ref_name)?;
+        let target = self.resolve_commit(repo, commit_id)?;
+
+        let mainline = match (target.parent_count(), mainline) {
+            (0 | 1, None) => 0,
+            (0 | 1, Some(_)) => {
+                return Err(AppError::BadRequest(format!(
+                    "Commit {} is not a merge, mainline is not allowed",
+                    target.id()
+                )));
+            }
+            (_, None) => {
+                return Err(AppError::BadRequest(format!(
+                    "Commit {} is a merge, mainline is required",
+                    target.id()
+                )));
+            }
+            (count, Some(m)) if m == 0 || m as usize > count => {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid mainline {}, commit {} has {} parents",
+                    m,
+                    target.id(),
+                    count
+                )));
+            }
+            (_, Some(m)) => m,
+        };
+
+        let mut index = match operation {
+            HistoryOperation::Revert => repo.revert_commit(&target, &ours, mainline, None),
+            _ => repo.cherrypick_commit(&target, &ours, mainline, None),
+        }
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to {} commit: {}", operation.name(), e))
+        })?;
+
+        if index.has_conflicts() {
+            let message = match operation {
+                HistoryOperation::Revert => "Revert conflict",
+                _ => "Cherry-pick conflict",
+            };
+            return Err(AppError::Conflict(
+                message.to_string(),
+                serde_json::json!({
+                    "branch": branch_name,
+                    "operation": operation,
+                    "commit": target.id().to_string(),
+                    "conflicts": self.collect_conflicts(&index)?,
+                }),
+            ));
+        }
+
+        let tree_oid = index
+            .write_tree_to(repo)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write tree: {}", e)))?;
+        if tree_oid == ours.tree_id() {
+            return Err(AppError::BadRequest(format!(
+                "Nothing to {}, changes of {} are already on {}",
+                operation.name(),
+                target.id(),
+                branch_name
+            )));
+        }
+        let tree = repo
+            .find_tree(tree_oid)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to find tree: {}", e)))?;
+
+        let signature = Signature::now(&committer.name, &committer.email).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create signature: {}", e))
+        })?;
+        let (author, message) = match operation {
+            HistoryOperation::Revert => (
+                signature.clone(),
+                format!(
+                    "Revert \"{}\"\n\nThis reverts commit {}.\n",
+                    target.summary().unwrap_or(""),
+                    target.id()
+                ),
+            ),
+            _ => (
+                target.author().to_owned(),
+                target.message().unwrap_or("").to_string(),
+            ),
+        };
+
+        let new_oid = repo
+            .commit(None, &author, &signature, &message, &tree, &[&ours])
+            .map_err(|e| AppError::InternalServerError(format!("Failed to commit: {}", e)))?;
+
+        self.move_branch(
+            repo,
+            &ref_name,
+            is_head,
+            new_oid,
+            &format!(
+                "{}: {}",
+                operation.name(),
+                message.lines().next().unwrap_or("")
+            ),
+        )?;
+
+        Ok(HistoryOperationResult {
+            branch: branch_name,
+            operation,
+            previous_head: ours.id().to_string(),
+            head: new_oid.to_string(),
+            backup_ref: None,
+        })
+    }
+}
@@ -0,0 +1,297 @@
+use std::path::Path;
+
+use git2::{
+    CheckoutNotificationType, ErrorCode, Oid, Repository, Signature, StashApplyOptions, StashFlags,
+    build::CheckoutBuilder,
+};
+
+use super::structs::{DiffSettings, StashDetail, StashEntry};
+use super::{GitConfig, GitManager};
+use crate::shared::error::AppError;
+
+// 储藏说明的格式为 "WIP on <分支>: ..." 或 "On <分支>: ..."
+fn stash_branch(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("WIP on ")
+        .or_else(|| message.strip_prefix("On "))?;
+    let (branch, _) = rest.split_once(':')?;
+    match branch {
+        "(no branch)" => None,
+        branch => Some(branch.to_string()),
+    }
+}
+
+impl GitManager {
+    fn stash_entries(&self, repo: &mut Repository) -> Result<Vec<StashEntry>, AppError> {
+        let mut stashes: Vec<(usize, String, Oid)> = Vec::new();
+        repo.stash_foreach(|index, message, id| {
+            stashes.push((index, message.to_string(), *id));
+            true
+        })
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list stashes: {}", e)))?;
+
+        let mut entries = Vec::with_capacity(stashes.len());
+        for (index, message, id) in stashes {
+            let commit = repo.find_commit(id).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to find stash commit: {}", e))
+            })?;
+            entries.push(StashEntry {
+                index,
+                id: id.to_string(),
+                branch: stash_branch(&message),
+                message,
+                base_commit: commit
+                    .parent_id(0)
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+                time: commit.time().seconds(),
+                includes_untracked: commit.parent_count() > 2,
+            });
+        }
+        Ok(entries)
+    }
+
+    // 按序号或储藏 ID 查找，提供 ID 时可以避免其他储藏增删导致序号变化
+    fn find_stash(
+        &self,
+        repo: &mut Repository,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+    ) -> Result<StashEntry, AppError> {
+        let entries = self.stash_entries(repo)?;
+        let found = match (stash_id, index) {
+            (Some(id), _) => entries.into_iter().find(|e| e.id == id),
+            (None, Some(index)) => entries.into_iter().find(|e| e.index == index),
+            (None, None) => {
+                return Err(AppError::BadRequest(
+                    "index or stash_id is required".to_string(),
+                ));
+            }
+        };
+        found.ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Stash not found: {}",
+                stash_id
+                    .map(|id| id.to_string())
+                    .or_else(|| index.map(|i| format!("stash@{{{}}}", i)))
+                    .unwrap_or_default()
+            ))
+        })
+    }
+
+    pub fn list_stashes(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+    ) -> Result<Vec<StashEntry>, AppError> {
+        let mut repo = self.open_worktree_repo(user_id, repo_name)?;
+        self.stash_entries(&mut repo)
+    }
+
+    // 储藏工作区和索引中的修改，完成后工作区恢复为 HEAD
+    pub fn save_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        message: Option<&str>,
+        include_untracked: bool,
+        keep_index: bool,
+        stasher: &GitConfig,
+    ) -> Result<StashEntry, AppError> {
+        let mut repo = self.open_worktree_repo(user_id, repo_name)?;
+
+        let signature = Signature::now(&stasher.name, &stasher.email).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create signature: {}", e))
+        })?;
+        let mut flags = StashFlags::DEFAULT;
+        if include_untracked {
+            flags |= StashFlags::INCLUDE_UNTRACKED;
+        }
+        if keep_index {
+            flags |= StashFlags::KEEP_INDEX;
+        }
+
+        repo.stash_save2(&signature, message, Some(flags))
+            .map_err(|e| match e.code() {
+                ErrorCode::NotFound => AppError::BadRequest("No local changes to save".to_string()),
+                ErrorCode::UnbornBranch => {
+                    AppError::BadRequest("Cannot stash in a repository without commits".to_string())
+                }
+                _ => AppError::InternalServerError(format!("Failed to save stash: {}", e)),
+            })?;
+
+        self.find_stash(&mut repo, Some(0), None)
+    }
+
+    // 储藏的修改以储藏时的 HEAD 为基准，与提交使用相同的结构化差异
+    pub fn show_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+        settings: &DiffSettings,
+    ) -> Result<StashDetail, AppError> {
+        let mut repo = self.open_worktree_repo(user_id, repo_name)?;
+        let stash = self.find_stash(&mut repo, index, stash_id)?;
+
+        let to_err =
+            |e: git2::Error| AppError::InternalServerError(format!("Failed to read stash: {}", e));
+        let commit = Oid::from_str(&stash.id)
+            .and_then(|id| repo.find_commit(id))
+            .map_err(to_err)?;
+        let base_tree = commit.parent(0).and_then(|p| p.tree()).map_err(to_err)?;
+        let stash_tree = commit.tree().map_err(to_err)?;
+
+        let mut diff = self.diff_trees(&repo, Some(&base_tree), Some(&stash_tree), settings)?;
+        // 第三个父提交保存未跟踪的文件
+        if let Ok(untracked) = commit.parent(2) {
+            let untracked_tree = untracked.tree().map_err(to_err)?;
+            let untracked_diff = self.diff_trees(&repo, None, Some(&untracked_tree), settings)?;
+            diff.merge(&untracked_diff).map_err(to_err)?;
+        }
+
+        let mut file_changes = Vec::new();
+        let stats =
+            self.process_diff_into_changes(&repo, &diff, &mut file_changes, &settings.limits)?;
+
+        Ok(StashDetail {
+            stash,
+            stats,
+            file_changes,
+        })
+    }
+
+    // 应用储藏，restore_index 时同时恢复暂存状态；会覆盖本地修改时不做任何改动，返回 409
+    // 与 git 相同，内容冲突会写入工作区和索引，此时 pop 也保留该储藏
+    pub fn apply_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+        restore_index: bool,
+        pop: bool,
+    ) -> Result<(), AppError> {
+        let mut repo = self.open_worktree_repo(user_id, repo_name)?;
+        let stash = self.find_stash(&mut repo, index, stash_id)?;
+        let conflict = |paths: Vec<String>| {
+            AppError::Conflict(
+                "Stash conflicts with local changes".to_string(),
+                serde_json::json!({
+                    "stash": stash.id,
+                    "paths": paths,
+                }),
+            )
+        };
+
+        // libgit2 会先写出未跟踪的文件再检查冲突，这里提前检查，保证冲突时工作区不变
+        let dirty_paths = self.stash_dirty_paths(&repo, &stash)?;
+        if !dirty_paths.is_empty() {
+            return Err(conflict(dirty_paths));
+        }
+
+        let mut conflict_paths = Vec::new();
+        let result = {
+            let mut checkout = CheckoutBuilder::new();
+            checkout
+                .safe()
+                .notify_on(CheckoutNotificationType::CONFLICT)
+                .notify(|_, path, _, _, _| {
+                    if let Some(path) = path {
+                        conflict_paths.push(path.to_string_lossy().into_owned());
+                    }
+                    true
+                });
+            let mut opts = StashApplyOptions::new();
+            opts.checkout_options(checkout);
+            if restore_index {
+                opts.reinstantiate_index();
+            }
+            repo.stash_apply(stash.index, Some(&mut opts))
+        };
+        result.map_err(|e| match e.code() {
+            ErrorCode::Conflict | ErrorCode::MergeConflict => conflict(conflict_paths),
+            _ => AppError::InternalServerError(format!("Failed to apply stash: {}", e)),
+        })?;
+
+        let has_conflicts = repo
+            .index()
+            .map(|index| index.has_conflicts())
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get index: {}", e)))?;
+        if pop && !has_conflicts {
+            repo.stash_drop(stash.index).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to drop stash: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    // 储藏涉及的文件在本地有修改，或储藏中的未跟踪文件在工作区已存在
+    fn stash_dirty_paths(
+        &self,
+        repo: &Repository,
+        stash: &StashEntry,
+    ) -> Result<Vec<String>, AppError> {
+        let to_err =
+            |e: git2::Error| AppError::InternalServerError(format!("Failed to read stash: {}", e));
+        let commit = Oid::from_str(&stash.id)
+            .and_then(|id| repo.find_commit(id))
+            .map_err(to_err)?;
+        let base_tree = commit.parent(0).and_then(|p| p.tree()).map_err(to_err)?;
+        let stash_tree = commit.tree().map_err(to_err)?;
+
+        let mut paths = Vec::new();
+        let diff = repo
+            .diff_tree_to_tree(Some(&base_tree), Some(&stash_tree), None)
+            .map_err(to_err)?;
+        if let Ok(untracked) = commit.parent(2) {
+            let untracked_tree = untracked.tree().map_err(to_err)?;
+            let untracked_diff = repo
+                .diff_tree_to_tree(None, Some(&untracked_tree), None)
+                .map_err(to_err)?;
+            paths.extend(
+                untracked_diff
+                    .deltas()
+                    .filter_map(|d| d.new_file().path())
+                    .map(Path::to_path_buf),
+            );
+        }
+        for delta in diff.deltas() {
+            paths.extend(
+                [delta.old_file().path(), delta.new_file().path()]
+                    .into_iter()
+                    .flatten()
+                    .map(Path::to_path_buf),
+            );
+        }
+        paths.sort();
+        paths.dedup();
+
+        let mut dirty = Vec::new();
+        for path in paths {
+            match repo.status_file(&path) {
+                Ok(status) if status.is_empty() => {}
+                Ok(_) => dirty.push(path.to_string_lossy().into_owned()),
+                Err(_) => {}
+            }
+        }
+        Ok(dirty)
+    }
+
+    pub fn drop_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+    ) -> Result<Vec<StashEntry>, AppError> {
+        let mut repo = self.open_worktree_repo(user_id, repo_name)?;
+        let stash = self.find_stash(&mut repo, index, stash_id)?;
+
+        repo.stash_drop(stash.index)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to drop stash: {}", e)))?;
+
+        self.stash_entries(&mut repo)
+    }
+}
@@ -0,0 +1,322 @@
+use std::path::Path;
+
+use git2::{
+    Commit, Diff, DiffOptions, ErrorCode, IndexAddOption, IndexEntry, IndexTime, Repository,
+    Status, StatusOptions, StatusShow,
+};
+
+use super::GitManager;
+use super::edit::edit_path;
+use super::structs::{CommitFileChange, DiffSettings, RepoStatus};
+use crate::shared::error::AppError;
+
+// 用户给出的路径按字面匹配，不作为 glob 展开；目录匹配其下的所有文件
+// 作为 add_all、update_all、remove_all 的回调，返回 1 表示跳过该文件
+fn literal_match(paths: &[String]) -> impl FnMut(&Path, &[u8]) -> i32 + '_ {
+    |path, _| match paths.iter().any(|p| path.starts_with(p)) {
+        true => 0,
+        false => 1,
+    }
+}
+
+impl GitManager {
+    // 需要工作区的操作不支持裸仓库
+    pub(super) fn open_worktree_repo(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+    ) -> Result<Repository, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+        if repo.is_bare() {
+            return Err(AppError::BadRequest(
+                "Bare repository has no working tree".to_string(),
+            ));
+        }
+        Ok(repo)
+    }
+
+    // 暂存的修改（HEAD 与索引）或未暂存的修改（索引与工作区）
+    // 未暂存的差异中包含未跟踪文件的内容，只在查看单个文件时使用
+    fn status_diff<'r>(
+        &self,
+        repo: &'r Repository,
+        staged: bool,
+        untracked: bool,
+        settings: &DiffSettings,
+    ) -> Result<Diff<'r>, AppError> {
+        let mut opts = Self::diff_options(settings);
+        let mut diff = if staged {
+            let head_tree = match repo.head() {
+                Ok(head) => Some(head.peel_to_tree().map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to get HEAD tree: {}", e))
+                })?),
+                Err(_) => None,
+            };
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))
+        } else {
+            opts.include_untracked(untracked)
+                .recurse_untracked_dirs(untracked)
+                .show_untracked_content(untracked);
+            repo.diff_index_to_workdir(None, Some(&mut opts))
+        }
+        .map_err(|e| AppError::InternalServerError(format!("Failed to compute diff: {}", e)))?;
+        Self::find_similar(&mut diff, settings)?;
+        Ok(diff)
+    }
+
+    // 列出暂存、未暂存、未跟踪、冲突和被忽略的文件，暂存和未暂存的文件带有差异
+    pub fn get_status(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        include_ignored: bool,
+        settings: &DiffSettings,
+    ) -> Result<RepoStatus, AppError> {
+        let repo = self.open_worktree_repo(user_id, repo_name)?;
+
+        let (branch, head) = match repo.head() {
+            Ok(head) => (
+                head.is_branch()
+                    .then(|| head.shorthand().map(|s| s.to_string()))
+                    .flatten(),
+                head.target().map(|oid| oid.to_string()),
+            ),
+            // 空仓库的 HEAD 指向尚无提交的分支
+            Err(e) if e.code() == ErrorCode::UnbornBranch => (
+                repo.find_reference("HEAD")
+                    .ok()
+                    .and_then(|h| h.symbolic_target().map(|t| t.to_string()))
+                    .map(|t| t.trim_start_matches("refs/heads/").to_string()),
+                None,
+            ),
+            Err(e) => {
+                return Err(AppError::InternalServerError(format!(
+                    "Failed to get HEAD: {}",
+                    e
+                )));
+            }
+        };
+
+        // 冲突文件只在 conflicted 中列出
+        let changes = |staged: bool| -> Result<Vec<CommitFileChange>, AppError> {
+            let diff = self.status_diff(&repo, staged, false, settings)?;
+            let mut files = Vec::new();
+            self.process_diff_into_changes(&repo, &diff, &mut files, &settings.limits)?;
+            files.retain(|f| f.status != "conflicted");
+            Ok(files)
+        };
+        let staged = changes(true)?;
+        let unstaged = changes(false)?;
+
+        let mut opts = StatusOptions::new();
+        opts.show(StatusShow::Workdir)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(include_ignored)
+            .recurse_ignored_dirs(false)
+            .exclude_submodules(true);
+        let statuses = repo
+            .statuses(Some(&mut opts))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get status: {}", e)))?;
+
+        let mut untracked = Vec::new();
+        let mut ignored = Vec::new();
+        for entry in statuses.iter() {
+            let path = String::from_utf8_lossy(entry.path_bytes()).into_owned();
+            if entry.status().contains(Status::WT_NEW) {
+                untracked.push(path);
+            } else if entry.status().contains(Status::IGNORED) {
+                ignored.push(path);
+            }
+        }
+
+        let index = repo
+            .index()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get index: {}", e)))?;
+        let conflicted = self.collect_conflicts(&index)?;
+
+        Ok(RepoStatus {
+            branch,
+            head,
+            staged,
+            unstaged,
+            untracked,
+            conflicted,
+            ignored,
+        })
+    }
+
+    // 单个文件暂存或未暂存的完整补丁，未跟踪的文件按新增显示
+    pub fn get_status_file_diff(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        path: &str,
+        staged: bool,
+        settings: &DiffSettings,
+    ) -> Result<CommitFileChange, AppError> {
+        let repo = self.open_worktree_repo(user_id, repo_name)?;
+
+        let diff = self.status_diff(&repo, staged, !staged, settings)?;
+        Self::file_change_in_diff(&diff, path)?
+            .ok_or_else(|| AppError::NotFound(format!("File has no changes: {}", path)))
+    }
+
+    // 把工作区中的文件或目录加入索引，已删除的文件从索引中移除，冲突文件加入后视为已解决
+    pub fn stage_paths(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        paths: &[String],
+    ) -> Result<(), AppError> {
+        let repo = self.open_worktree_repo(user_id, repo_name)?;
+        let paths = paths
+            .iter()
+            .map(|p| edit_path(p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut index = repo
+            .index()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get index: {}", e)))?;
+        index
+            .add_all(
+                paths.iter(),
+                IndexAddOption::DISABLE_PATHSPEC_MATCH,
+                Some(&mut literal_match(&paths)),
+            )
+            .map_err(|e| AppError::InternalServerError(format!("Failed to add files: {}", e)))?;
+        index
+            .update_all(paths.iter(), Some(&mut literal_match(&paths)))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to update index: {}", e)))?;
+        index.write().map_err(|e| {
+            AppError::InternalServerError(format!("Failed to write index file: {}", e))
+        })
+    }
+
+    // 把索引中的文件恢复为 HEAD 中的版本，工作区不变
+    pub fn unstage_paths(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        paths: &[String],
+    ) -> Result<(), AppError> {
+        let repo = self.open_worktree_repo(user_id, repo_name)?;
+        let paths = paths
+            .iter()
+            .map(|p| edit_path(p))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match repo.head().and_then(|h| h.peel_to_commit()) {
+            Ok(head) => Self::reset_index_paths(&repo, &head, &paths),
+            // 空仓库中直接从索引移除
+            Err(_) => repo.index().and_then(|mut index| {
+                index.remove_all(paths.iter(), Some(&mut literal_match(&paths)))?;
+                index.write()
+            }),
+        }
+        .map_err(|e| AppError::InternalServerError(format!("Failed to unstage files: {}", e)))
+    }
+
+    // 与 git_reset_default 相同，但路径按字面匹配：
+    // HEAD 中存在的文件恢复为 HEAD 中的版本，不存在的从索引移除
+    fn reset_index_paths(
+        repo: &Repository,
+        head: &Commit,
+        paths: &[String],
+    ) -> Result<(), git2::Error> {
+        let tree = head.tree()?;
+        let mut index = repo.index()?;
+        let mut opts = DiffOptions::new();
+        opts.disable_pathspec_match(true);
+        for path in paths {
+            opts.pathspec(path);
+        }
+        let diff = repo.diff_tree_to_index(Some(&tree), Some(&index), Some(&mut opts))?;
+
+        for delta in diff.deltas() {
+            let file = match delta.old_file().path() {
+                Some(_) => delta.old_file(),
+                None => delta.new_file(),
+            };
+            let (Some(path), Some(path_bytes)) = (file.path(), file.path_bytes()) else {
+                continue;
+            };
+            // remove_path 同时清除冲突的各个版本
+            index.remove_path(path)?;
+            if let Ok(entry) = tree.get_path(path) {
+                index.add(&IndexEntry {
+                    ctime: IndexTime::new(0, 0),
+                    mtime: IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: entry.filemode() as u32,
+                    uid: 0,
+                    gid: 0,
+                    file_size: 0,
+                    id: entry.id(),
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path_bytes.to_vec(),
+                })?;
+            }
+        }
+        index.write()
+    }
+
+    // 丢弃工作区中未暂存的修改，恢复为索引中的版本；未跟踪的文件和目录直接删除
+    pub fn discard_paths(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        paths: &[String],
+    ) -> Result<(), AppError> {
+        let repo = self.open_worktree_repo(user_id, repo_name)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| AppError::BadRequest("Bare repository has no working tree".into()))?
+            .to_path_buf();
+
+        // 先检查所有路径，有路径被拒绝时不修改任何文件
+        let mut remove_files = Vec::new();
+        let mut checkout_paths = Vec::new();
+        for path in paths {
+            let path = edit_path(path)?;
+            match repo.status_file(Path::new(&path)) {
+                Ok(status) if status.contains(Status::CONFLICTED) => {
+                    return Err(AppError::BadRequest(format!(
+                        "Cannot discard conflicted file: {}",
+                        path
+                    )));
+                }
+                Ok(status) if status.contains(Status::WT_NEW) => remove_files.push(path),
+                Err(e) if e.code() == ErrorCode::NotFound && !workdir.join(&path).is_dir() => {
+                    return Err(AppError::NotFound(format!("File not found: {}", path)));
+                }
+                // 目录按路径前缀恢复其中的所有文件
+                _ => checkout_paths.push(path),
+            }
+        }
+
+        for path in &remove_files {
+            std::fs::remove_file(workdir.join(path)).map_err(|e| {
+                AppError::InternalServerError(format!("Failed to remove {}: {}", path, e))
+            })?;
+        }
+        if checkout_paths.is_empty() {
+            return Ok(());
+        }
+
+        // 目录中未跟踪的文件一并删除
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout
+            .force()
+            .remove_untracked(true)
+            .disable_pathspec_match(true);
+        for path in &checkout_paths {
+            checkout.path(path);
+        }
+        repo.checkout_index(None, Some(&mut checkout))
+            .map_err(|e| AppError::InternalServerError(format!("Failed to discard changes: {}", e)))
+    }
+}
@@ -1,6 +1,8 @@
 use std::{collections::HashMap, hash::Hash, sync::Arc};
 
 use futures::channel::mpsc::Sender;
+use git2::Oid;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, mpsc};
 use tracing::{error, info};
@@ -13,6 +15,73 @@ pub struct CommitInfo {
     pub time: i64,
 }
 
+// blame 中连续且来自同一提交的若干行，行号从 1 开始
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameHunk {
+    pub start_line: usize,
+    pub lines: usize,
+    pub commit_id: String,
+    pub author: String,
+    pub time: i64,
+    pub orig_path: String,      // 该行在原提交中的路径
+    pub orig_start_line: usize, // 该行在原提交中的行号
+    pub boundary: bool,         // 到达历史边界，提交不一定是真正的来源
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlameResult {
+    pub path: String,
+    pub commit_id: String,
+    pub blob_id: String,
+    pub total_lines: usize,
+    pub hunks: Vec<BlameHunk>,
+}
+
+// 文件历史中的一条记录，path 为该提交中文件的路径
+#[derive(Debug, Serialize)]
+pub struct FileHistoryEntry {
+    pub commit_info: CommitInfo,
+    pub path: String,
+    pub old_path: Option<String>, // 重命名前的路径
+    pub status: String,           // added、modified、deleted、renamed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BranchKind {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchInfo {
+    pub name: String,
+    pub kind: BranchKind,
+    pub is_head: bool,
+    pub upstream: Option<String>, // 仅本地分支，例如 origin/main
+    pub ahead: Option<usize>,     // 相对上游领先的提交数
+    pub behind: Option<usize>,    // 相对上游落后的提交数
+    pub tip: CommitInfo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagKind {
+    Lightweight,
+    Annotated,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagInfo {
+    pub name: String,
+    pub kind: TagKind,
+    pub target: CommitInfo,
+    pub tagger: Option<String>, // 仅附注标签
+    pub message: Option<String>,
+    pub time: Option<i64>,
+    pub release_notes: Option<String>, // 发布说明，保存在数据库中
+}
+
 #[derive(Debug, Serialize)]
 pub struct GitOperationResult {
     pub success: bool,
@@ -24,24 +93,414 @@ pub struct GitOperationResult {
 pub struct GitFileEntry {
     pub name: String,
     pub path: String,
+    pub id: String, // blob 或 tree 的对象 ID
     pub is_dir: bool,
+    pub filemode: i32, // 0o100644 普通文件、0o100755 可执行文件、0o120000 符号链接等
     pub size: Option<u64>, // 仅对文件有效
     pub children: Vec<GitFileEntry>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CommitHistoryPage {
+    pub commits: Vec<CommitInfo>,
+    pub next_cursor: Option<String>, // 下一页的游标，没有更多提交时为空
+}
+
+// 从本行的 from_column 连到下一行的 to_column
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub from_column: usize,
+    pub to_column: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GraphNode {
+    pub commit_info: CommitInfo,
+    pub parents: Vec<String>,
+    pub column: usize,         // 节点所在的列，从 0 开始
+    pub edges: Vec<GraphEdge>, // 本行到下一行的所有连线
+    pub refs: Vec<String>,     // 指向该提交的 HEAD、分支和标签
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitGraphPage {
+    pub nodes: Vec<GraphNode>,
+    pub next_cursor: Option<String>, // 本页最后一个提交，没有更多提交时为空
+}
+
+#[derive(Debug, Serialize)]
+pub struct GitTreePage {
+    pub path: String, // 当前目录，根目录为空
+    pub total: usize, // 当前目录的条目总数
+    pub entries: Vec<GitFileEntry>,
+    pub next_cursor: Option<String>, // 下一页的游标，没有更多条目时为空
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileContent {
+    pub id: String, // blob 的对象 ID
+    pub binary: bool,
+    pub size: u64,
+    pub content: Option<String>, // 二进制文件不返回内容
+}
+
+// 原始文件下载前读取的 blob 信息，不含内容
+#[derive(Debug)]
+pub struct BlobMeta {
+    pub id: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "application/zip",
+            ArchiveFormat::TarGz => "application/gzip",
+        }
+    }
+}
+
+impl TryFrom<String> for ArchiveFormat {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "zip" => Ok(ArchiveFormat::Zip),
+            "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+            _ => Err(format!("Invalid archive format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ArchiveInfo {
+    pub file_name: String,
+    pub commit_id: String, // 打包的提交
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Addition,
+    Deletion,
+    NoNewline, // 文件末尾没有换行
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub old_lineno: Option<u32>, // 新增行没有旧行号
+    pub new_lineno: Option<u32>, // 删除行没有新行号
+    pub content: String,         // 不含行尾换行符
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffHunk {
+    pub header: String, // 例如 @@ -1,3 +1,4 @@ fn main()
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CommitFileChange {
-    pub path: String,
-    pub status: String,       // "added", "modified", "deleted" 等
-    pub diff: Option<String>, // 文件差异内容
+    pub path: String,             // 新路径，删除的文件为旧路径
+    pub old_path: Option<String>, // 新增的文件没有旧路径
+    pub new_path: Option<String>, // 删除的文件没有新路径
+    pub status: String,           // "added", "modified", "deleted" 等
+    pub old_mode: Option<String>, // 八进制文件模式，例如 100644
+    pub new_mode: Option<String>,
+    pub binary: bool, // 二进制文件没有 hunks
+    pub additions: usize,
+    pub deletions: usize,
+    pub truncated: bool, // 超出大小限制，hunks 为空，需要单独加载
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WhitespaceMode {
+    #[default]
+    None,
+    IgnoreAll,    // 忽略所有空白，等同 git diff -w
+    IgnoreChange, // 忽略空白数量的变化，等同 -b
+    IgnoreEol,    // 忽略行尾空白
+}
+
+impl TryFrom<String> for WhitespaceMode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(WhitespaceMode::None),
+            "ignore_all" => Ok(WhitespaceMode::IgnoreAll),
+            "ignore_change" => Ok(WhitespaceMode::IgnoreChange),
+            "ignore_eol" => Ok(WhitespaceMode::IgnoreEol),
+            _ => Err(format!("Invalid whitespace mode: {}", s)),
+        }
+    }
+}
+
+// 生成差异时的选项，对应 git2 的 DiffOptions 和 DiffFindOptions
+#[derive(Debug, Clone)]
+pub struct DiffSettings {
+    pub whitespace: WhitespaceMode,
+    pub context_lines: Option<u32>, // 默认 3 行
+    pub find_renames: bool,
+    pub find_copies: bool,
+    pub similarity: Option<u16>, // 重命名、复制的相似度阈值 0-100，默认 50
+    pub pathspec: Vec<String>,   // 只比较匹配的路径
+    pub limits: DiffLimits,
+}
+
+impl Default for DiffSettings {
+    fn default() -> Self {
+        DiffSettings {
+            whitespace: WhitespaceMode::None,
+            context_lines: None,
+            find_renames: true,
+            find_copies: false,
+            similarity: None,
+            pathspec: Vec::new(),
+            limits: DiffLimits::unlimited(),
+        }
+    }
+}
+
+// 大差异保护，超出限制的文件只返回元信息
+#[derive(Debug, Clone, Copy)]
+pub struct DiffLimits {
+    pub max_files: usize,
+    pub max_lines_per_file: usize,
+    pub max_bytes_per_file: usize,
+}
+
+impl DiffLimits {
+    pub fn unlimited() -> Self {
+        DiffLimits {
+            max_files: usize::MAX,
+            max_lines_per_file: usize::MAX,
+            max_bytes_per_file: usize::MAX,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DiffStats {
+    pub files_changed: usize,
+    pub additions: usize, // 不含超出文件数限制的文件
+    pub deletions: usize,
+    pub truncated: bool, // 是否有文件被截断
 }
 
 #[derive(Debug, Serialize)]
 pub struct CommitDetail {
     pub commit_info: CommitInfo,
+    pub stats: DiffStats,
     pub file_changes: Vec<CommitFileChange>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeFilter {
+    #[default]
+    Show, // 显示合并提交
+    Hide, // 隐藏合并提交，相当于 --no-merges
+    Only, // 只显示合并提交，相当于 --merges
+}
+
+impl TryFrom<String> for MergeFilter {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "show" => Ok(MergeFilter::Show),
+            "hide" => Ok(MergeFilter::Hide),
+            "only" => Ok(MergeFilter::Only),
+            _ => Err(format!("Invalid merges filter: {}", s)),
+        }
+    }
+}
+
+// 提交历史的过滤条件，全部为空时返回起始版本的完整历史
+#[derive(Debug, Default)]
+pub struct CommitFilter {
+    pub rev: Option<String>,       // 起始版本，默认 HEAD
+    pub author: Option<String>,    // 作者名或邮箱，不区分大小写的子串
+    pub committer: Option<String>, // 提交者名或邮箱，不区分大小写的子串
+    pub message: Option<Regex>,    // 提交说明，子串搜索时已转义
+    pub since: Option<i64>,        // 提交时间不早于，Unix 秒
+    pub until: Option<i64>,        // 提交时间不晚于，Unix 秒
+    pub path: Option<String>,      // 只保留修改过该文件或目录的提交
+    pub merges: MergeFilter,
+}
+
+impl CommitFilter {
+    pub fn is_empty(&self) -> bool {
+        self.rev.is_none()
+            && self.author.is_none()
+            && self.committer.is_none()
+            && self.message.is_none()
+            && self.since.is_none()
+            && self.until.is_none()
+            && self.path.is_none()
+            && self.merges == MergeFilter::Show
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMode {
+    TwoDot,   // 直接比较 base 和 head 的树
+    ThreeDot, // 比较合并基础和 head 的树，只看 head 分支上的改动
+}
+
+impl TryFrom<String> for CompareMode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "two_dot" | ".." => Ok(CompareMode::TwoDot),
+            "three_dot" | "..." => Ok(CompareMode::ThreeDot),
+            _ => Err(format!("Invalid compare mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareResult {
+    pub base: CommitInfo,
+    pub head: CommitInfo,
+    pub mode: CompareMode,
+    pub merge_base: Option<String>, // 没有共同祖先时为空
+    pub ahead_by: usize,            // head 中有而 base 中没有的提交数
+    pub behind_by: usize,           // base 中有而 head 中没有的提交数
+    pub commits: Vec<CommitInfo>,   // head 中有而 base 中没有的提交，从旧到新
+    pub stats: DiffStats,
+    pub file_changes: Vec<CommitFileChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushRefStatus {
+    pub reference: String,
+    pub success: bool,
+    pub message: Option<String>, // 远端拒绝时的原因
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushResult {
+    pub remote: String,
+    pub branch: String,
+    pub refs: Vec<PushRefStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryOperation {
+    Revert,
+    CherryPick,
+    Reset,
+}
+
+impl HistoryOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HistoryOperation::Revert => "revert",
+            HistoryOperation::CherryPick => "cherry-pick",
+            HistoryOperation::Reset => "reset",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetMode {
+    Soft,  // 只移动分支
+    Mixed, // 移动分支并重置索引，保留工作区
+    Hard,  // 移动分支并重置索引和工作区
+}
+
+impl TryFrom<String> for ResetMode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "soft" => Ok(ResetMode::Soft),
+            "mixed" => Ok(ResetMode::Mixed),
+            "hard" => Ok(ResetMode::Hard),
+            _ => Err(format!("Invalid reset mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryOperationResult {
+    pub branch: String,
+    pub operation: HistoryOperation,
+    pub previous_head: String,      // 操作前分支指向的提交
+    pub head: String,               // 操作后分支指向的提交
+    pub backup_ref: Option<String>, // hard 重置前保存旧指向的引用
+}
+
+// 合并、变基等操作产生冲突的文件
+#[derive(Debug, Serialize)]
+pub struct ConflictEntry {
+    pub path: String,
+    pub ancestor_id: Option<String>, // 共同祖先中的 blob
+    pub our_id: Option<String>,      // 本地版本的 blob
+    pub their_id: Option<String>,    // 对方版本的 blob
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullStrategy {
+    Merge,
+    Rebase,
+}
+
+impl TryFrom<String> for PullStrategy {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "merge" => Ok(PullStrategy::Merge),
+            "rebase" => Ok(PullStrategy::Rebase),
+            _ => Err(format!("Invalid pull strategy: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullOutcome {
+    UpToDate,
+    FastForward,
+    Merged,
+    Rebased,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PullResult {
+    pub branch: String,
+    pub strategy: PullStrategy,
+    pub outcome: PullOutcome,
+    pub head: String, // 拉取后分支指向的提交
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct WebSocketMsg {
     pub user_id: String,
@@ -106,6 +565,19 @@ impl WebSocketManager {
         }
     }
 
+    pub async fn send_repo_event(&self, user_id: &str, repo_name: &str, event: &str) {
+        let message = WebSocketMsg {
+            user_id: user_id.to_string(),
+            repo_name: repo_name.to_string(),
+            message: event.to_string(),
+        };
+        self.send_message(user_id, message).await;
+        info!(
+            "Sent repo event for user: {}, repo: {}, event: {}",
+            user_id, repo_name, event
+        );
+    }
+
     pub async fn send_clone_status(&self, user_id: &str, repo_name: &str, status: &str) {
         let message = WebSocketMsg {
             user_id: user_id.to_string(),
@@ -119,3 +591,136 @@ impl WebSocketManager {
         );
     }
 }
+
+// 在线编辑单个文件，base_blob_id 和 base_commit_id 至少提供一个，同时提供时以 blob 为准
+#[derive(Debug)]
+pub struct FileEdit {
+    pub path: String,
+    pub content: String,
+    pub message: String,
+    pub branch: Option<String>,         // 提交到的分支，默认当前分支
+    pub base_blob_id: Option<String>,   // 开始编辑时文件的 blob
+    pub base_commit_id: Option<String>, // 开始编辑时的提交，文件在其中不存在表示新建
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileEditResult {
+    pub path: String,
+    pub branch: String,
+    pub blob_id: String,            // 写入后文件的 blob
+    pub commit_id: String,          // 新提交
+    pub previous_commit_id: String, // 编辑前分支指向的提交
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileActionKind {
+    Create, // 新建文件，路径已存在时报错
+    Update, // 修改已有文件的内容
+    Delete,
+    Move,  // 从 previous_path 移动到 path，可同时修改内容
+    Chmod, // 只修改可执行位
+}
+
+impl TryFrom<String> for FileActionKind {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "create" => Ok(FileActionKind::Create),
+            "update" => Ok(FileActionKind::Update),
+            "delete" => Ok(FileActionKind::Delete),
+            "move" => Ok(FileActionKind::Move),
+            "chmod" => Ok(FileActionKind::Chmod),
+            _ => Err(format!("Invalid file action: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FileAction {
+    pub action: FileActionKind,
+    pub path: String,
+    pub previous_path: Option<String>, // move 的原路径
+    pub content: Option<String>,       // create、update 必填，move 可选
+    pub blob_id: Option<Oid>,          // 已写入对象库的内容，优先于 content
+    pub executable: Option<bool>,      // chmod 必填，create 可选
+}
+
+// 一次提交多个文件的修改，按顺序应用，后面的操作可以看到前面操作的结果
+#[derive(Debug)]
+pub struct FileBatch {
+    pub message: String,
+    pub branch: Option<String>,         // 提交到的分支，默认当前分支
+    pub base_commit_id: Option<String>, // 涉及的文件在此提交之后被修改过时拒绝提交
+    pub update_workdir: bool,           // 提交到当前分支时是否同步索引和工作区
+    pub actions: Vec<FileAction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileBatchResult {
+    pub branch: String,
+    pub commit_id: String,
+    pub previous_commit_id: Option<String>, // 分支原来指向的提交，空仓库时为空
+}
+
+// 工作区状态，staged 为索引相对 HEAD 的修改，unstaged 为工作区相对索引的修改
+#[derive(Debug, Serialize)]
+pub struct RepoStatus {
+    pub branch: Option<String>, // 分离 HEAD 时为空
+    pub head: Option<String>,   // HEAD 指向的提交，空仓库时为空
+    pub staged: Vec<CommitFileChange>,
+    pub unstaged: Vec<CommitFileChange>,
+    pub untracked: Vec<String>,
+    pub conflicted: Vec<ConflictEntry>,
+    pub ignored: Vec<String>, // 只在请求时返回，被忽略的目录不展开
+}
+
+#[derive(Debug, Serialize)]
+pub struct StashEntry {
+    pub index: usize, // stash@{index}，新的储藏在前
+    pub id: String,
+    pub message: String,
+    pub branch: Option<String>, // 储藏时所在的分支
+    pub base_commit: String,    // 储藏时 HEAD 指向的提交
+    pub time: i64,
+    pub includes_untracked: bool,
+}
+
+// 储藏相对储藏时 HEAD 的修改，包含未跟踪文件时一并列出
+#[derive(Debug, Serialize)]
+pub struct StashDetail {
+    pub stash: StashEntry,
+    pub stats: DiffStats,
+    pub file_changes: Vec<CommitFileChange>,
+}
+
+// 上传文件到 directory 下并提交
+#[derive(Debug)]
+pub struct FileUpload {
+    pub directory: String,
+    pub message: Option<String>,        // 默认根据上传的文件生成
+    pub branch: Option<String>,         // 提交到的分支，默认当前分支
+    pub base_commit_id: Option<String>, // 与批量提交相同的并发检查
+    pub overwrite: bool,                // 是否允许覆盖已有文件
+    pub update_workdir: bool,
+}
+
+// 上传大小限制，由配置文件决定
+#[derive(Debug, Clone, Copy)]
+pub struct UploadLimits {
+    pub max_file_size: u64,  // 单个文件的最大字节数
+    pub max_total_size: u64, // 一次上传所有文件的最大字节数
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadedFile {
+    pub path: String,
+    pub blob_id: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadResult {
+    pub commit: FileBatchResult,
+    pub files: Vec<UploadedFile>,
+}
@@ -0,0 +1,139 @@
+use git2::Signature;
+use tracing::info;
+
+use super::structs::{TagInfo, TagKind};
+use super::{GitConfig, GitManager};
+use crate::shared::error::AppError;
+
+impl GitManager {
+    pub fn list_tags(&self, user_id: &str, repo_name: &str) -> Result<Vec<TagInfo>, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let names = repo
+            .tag_names(None)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tags: {}", e)))?;
+
+        let mut tags = Vec::new();
+        for name in names.iter().flatten() {
+            let reference = repo
+                .find_reference(&format!("refs/tags/{}", name))
+                .map_err(|e| AppError::InternalServerError(format!("Failed to get tag: {}", e)))?;
+
+            // 只处理指向提交的标签
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+
+            let tag = match reference.peel_to_tag() {
+                Ok(tag) => TagInfo {
+                    name: name.to_string(),
+                    kind: TagKind::Annotated,
+                    target: Self::to_commit_info(&commit),
+                    tagger: tag.tagger().map(|t| {
+                        format!("{} <{}>", t.name().unwrap_or(""), t.email().unwrap_or(""))
+                    }),
+                    message: tag.message().map(|m| m.to_string()),
+                    time: tag.tagger().map(|t| t.when().seconds()),
+                    release_notes: None,
+                },
+                Err(_) => TagInfo {
+                    name: name.to_string(),
+                    kind: TagKind::Lightweight,
+                    target: Self::to_commit_info(&commit),
+                    tagger: None,
+                    message: None,
+                    time: None,
+                    release_notes: None,
+                },
+            };
+            tags.push(tag);
+        }
+
+        // 最新的标签排在前面
+        tags.sort_by(|a, b| {
+            let a_time = a.time.unwrap_or(a.target.time);
+            let b_time = b.time.unwrap_or(b.target.time);
+            b_time.cmp(&a_time)
+        });
+
+        Ok(tags)
+    }
+
+    // 有 message 时创建附注标签，否则创建轻量标签
+    pub fn create_tag(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        tag_name: &str,
+        target: Option<&str>,
+        message: Option<&str>,
+        tagger: &GitConfig,
+    ) -> Result<(), AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit(&repo, target.unwrap_or("HEAD"))?;
+
+        if repo
+            .find_reference(&format!("refs/tags/{}", tag_name))
+            .is_ok()
+        {
+            return Err(AppError::BadRequest(format!(
+                "Tag already exists: {}",
+                tag_name
+            )));
+        }
+
+        let result = match message {
+            Some(message) => {
+                let signature = Signature::now(&tagger.name, &tagger.email).map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to create signature: {}", e))
+                })?;
+                repo.tag(tag_name, commit.as_object(), &signature, message, false)
+            }
+            None => repo.tag_lightweight(tag_name, commit.as_object(), false),
+        };
+        result.map_err(|e| AppError::BadRequest(format!("Failed to create tag: {}", e)))?;
+
+        info!("Created tag {} at {}", tag_name, commit.id());
+        Ok(())
+    }
+
+    pub fn delete_tag(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        tag_name: &str,
+    ) -> Result<(), AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        if repo
+            .find_reference(&format!("refs/tags/{}", tag_name))
+            .is_err()
+        {
+            return Err(AppError::NotFound(format!("Tag not found: {}", tag_name)));
+        }
+
+        repo.tag_delete(tag_name)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to delete tag: {}", e)))?;
+
+        info!("Deleted tag {}", tag_name);
+        Ok(())
+    }
+
+    pub fn tag_exists(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        tag_name: &str,
+    ) -> Result<bool, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        Ok(repo
+            .find_reference(&format!("refs/tags/{}", tag_name))
+            .is_ok())
+    }
+}
@@ -0,0 +1,140 @@
+use git2::{ObjectType, Repository};
+
+use super::GitManager;
+use super::structs::{GitFileEntry, GitTreePage};
+use crate::shared::error::AppError;
+
+// 单层目录分页的默认和最大条目数
+pub const DEFAULT_TREE_PAGE_SIZE: usize = 100;
+pub const MAX_TREE_PAGE_SIZE: usize = 1000;
+
+// 去掉首尾的斜杠，根目录为空字符串
+pub fn normalize_tree_path(path: &str) -> String {
+    path.trim().trim_matches('/').to_string()
+}
+
+// 只读取对象头获取大小，不加载 blob 内容
+pub(super) fn tree_entry_info(
+    odb: &git2::Odb,
+    entry: &git2::TreeEntry,
+    prefix: &str,
+) -> GitFileEntry {
+    let name = String::from_utf8_lossy(entry.name_bytes()).to_string();
+    let path = if prefix.is_empty() {
+        name.clone()
+    } else {
+        format!("{}/{}", prefix, name)
+    };
+
+    let is_dir = entry.kind() == Some(ObjectType::Tree);
+    // 子模块条目指向其他仓库的提交，本仓库中没有该对象
+    let size = match entry.kind() {
+        Some(ObjectType::Blob) => odb
+            .read_header(entry.id())
+            .ok()
+            .map(|(size, _)| size as u64),
+        _ => None,
+    };
+
+    GitFileEntry {
+        name,
+        path,
+        id: entry.id().to_string(),
+        is_dir,
+        filemode: entry.filemode(),
+        size,
+        children: Vec::new(),
+    }
+}
+
+impl GitManager {
+    // 按路径查找子目录的树，空路径返回根树
+    pub(super) fn find_subtree<'r>(
+        &self,
+        repo: &'r Repository,
+        tree: &git2::Tree<'r>,
+        path: &str,
+    ) -> Result<git2::Tree<'r>, AppError> {
+        if path.is_empty() {
+            return Ok(tree.clone());
+        }
+
+        let entry = tree
+            .get_path(std::path::Path::new(path))
+            .map_err(|_| AppError::NotFound(format!("Directory not found: {}", path)))?;
+        if entry.kind() != Some(ObjectType::Tree) {
+            return Err(AppError::BadRequest(format!(
+                "Path is not a directory: {}",
+                path
+            )));
+        }
+
+        repo.find_tree(entry.id())
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))
+    }
+
+    // 只列出一层目录，目录在前、文件在后，按名称排序并按游标分页
+    pub fn list_tree_page(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        directory_path: Option<&str>,
+        rev: Option<&str>,
+        cursor: Option<&str>, // 上一页最后一个条目的名称
+        limit: usize,
+    ) -> Result<GitTreePage, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let commit = self.resolve_commit_or_head(&repo, rev)?;
+        let root = commit
+            .tree()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to get tree: {}", e)))?;
+
+        let path = normalize_tree_path(directory_path.unwrap_or(""));
+        let tree = self.find_subtree(&repo, &root, &path)?;
+
+        let mut entries: Vec<git2::TreeEntry> = tree.iter().collect();
+        entries.sort_by(|a, b| {
+            let a_file = a.kind() != Some(ObjectType::Tree);
+            let b_file = b.kind() != Some(ObjectType::Tree);
+            a_file
+                .cmp(&b_file)
+                .then_with(|| a.name_bytes().cmp(b.name_bytes()))
+        });
+
+        let start = match cursor {
+            Some(cursor) if !cursor.is_empty() => {
+                entries
+                    .iter()
+                    .position(|e| e.name_bytes() == cursor.as_bytes())
+                    .ok_or_else(|| AppError::BadRequest(format!("Invalid cursor: {}", cursor)))?
+                    + 1
+            }
+            _ => 0,
+        };
+        let limit = limit.clamp(1, MAX_TREE_PAGE_SIZE);
+        let end = (start + limit).min(entries.len());
+
+        let odb = repo
+            .odb()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to open odb: {}", e)))?;
+        let page: Vec<GitFileEntry> = entries[start..end]
+            .iter()
+            .map(|entry| tree_entry_info(&odb, entry, &path))
+            .collect();
+
+        let next_cursor = if end < entries.len() {
+            page.last().map(|entry| entry.name.clone())
+        } else {
+            None
+        };
+
+        Ok(GitTreePage {
+            path,
+            total: entries.len(),
+            entries: page,
+            next_cursor,
+        })
+    }
+}
@@ -0,0 +1,192 @@
+use std::io::Write;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::{StreamExt, channel::mpsc};
+use git2::{BlobWriter, Oid, Repository};
+
+use super::edit::edit_path;
+use super::history::entry_at;
+use super::structs::{
+    FileAction, FileActionKind, FileBatch, FileUpload, UploadLimits, UploadResult, UploadedFile,
+};
+use super::tree::normalize_tree_path;
+use super::{GitConfig, GitManager};
+use crate::shared::error::AppError;
+
+// 通道中最多缓存的数据块数，写入对象库慢时请求体的读取会等待
+pub const UPLOAD_CHANNEL_SIZE: usize = 8;
+
+// 等待下一个数据块的最长时间，客户端停止发送时释放写入线程
+const UPLOAD_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 请求体按顺序解析为文件开始、数据块和结束，没有收到 Finish 说明上传被中断
+pub enum UploadEvent {
+    File(String),
+    Chunk(Bytes),
+    Finish,
+}
+
+pub type UploadStream = mpsc::Receiver<UploadEvent>;
+
+// 正在写入对象库的文件
+struct PendingFile<'r> {
+    path: String,
+    size: u64,
+    writer: BlobWriter<'r>,
+}
+
+impl GitManager {
+    // 边接收边把文件内容写入对象库，全部接收完后作为一次提交写入分支
+    // 超出大小限制、上传中断或超时时不会提交，已写入的对象由 gc 清理
+    // 在阻塞线程中运行，等待数据块时使用调用方的 tokio 运行时计时
+    pub fn upload_files(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        upload: &FileUpload,
+        limits: &UploadLimits,
+        mut files: UploadStream,
+        committer: &GitConfig,
+    ) -> Result<UploadResult, AppError> {
+        let repo_path = self.get_user_repo_path(user_id, repo_name);
+        let repo = self.open_repo(&repo_path)?;
+
+        let directory = normalize_tree_path(&upload.directory);
+        let mut uploaded: Vec<(Oid, UploadedFile)> = Vec::new();
+        let mut pending: Option<PendingFile> = None;
+        let mut total_size: u64 = 0;
+
+        let runtime = tokio::runtime::Handle::current();
+        loop {
+            let event = runtime
+                .block_on(tokio::time::timeout(UPLOAD_IDLE_TIMEOUT, files.next()))
+                .map_err(|_| AppError::BadRequest("Upload timed out".to_string()))?
+                .ok_or_else(|| AppError::BadRequest("Upload was interrupted".to_string()))?;
+            match event {
+                UploadEvent::File(name) => {
+                    if let Some(file) = pending.take() {
+                        uploaded.push(Self::finish_upload(file)?);
+                    }
+                    let path = match directory.as_str() {
+                        "" => edit_path(&name)?,
+                        dir => edit_path(&format!("{}/{}", dir, name))?,
+                    };
+                    if uploaded.iter().any(|(_, f)| f.path == path) {
+                        return Err(AppError::BadRequest(format!(
+                            "Duplicate file in upload: {}",
+                            path
+                        )));
+                    }
+                    let writer = repo.blob_writer(None).map_err(|e| {
+                        AppError::InternalServerError(format!("Failed to write blob: {}", e))
+                    })?;
+                    pending = Some(PendingFile {
+                        path,
+                        size: 0,
+                        writer,
+                    });
+                }
+                UploadEvent::Chunk(data) => {
+                    let file = pending.as_mut().ok_or_else(|| {
+                        AppError::BadRequest("Upload data without a file".to_string())
+                    })?;
+                    file.size += data.len() as u64;
+                    total_size += data.len() as u64;
+                    if file.size > limits.max_file_size {
+                        return Err(AppError::BadRequest(format!(
+                            "File {} exceeds the size limit of {} bytes",
+                            file.path, limits.max_file_size
+                        )));
+                    }
+                    if total_size > limits.max_total_size {
+                        return Err(AppError::BadRequest(format!(
+                            "Upload exceeds the total size limit of {} bytes",
+                            limits.max_total_size
+                        )));
+                    }
+                    file.writer.write_all(&data).map_err(|e| {
+                        AppError::InternalServerError(format!("Failed to write blob: {}", e))
+                    })?;
+                }
+                UploadEvent::Finish => break,
+            }
+        }
+        if let Some(file) = pending.take() {
+            uploaded.push(Self::finish_upload(file)?);
+        }
+        if uploaded.is_empty() {
+            return Err(AppError::BadRequest("No files uploaded".to_string()));
+        }
+
+        let actions = self.upload_actions(&repo, upload, &uploaded)?;
+        let message = match (
+            upload.message.as_deref().map(str::trim),
+            uploaded.as_slice(),
+        ) {
+            (Some(message), _) if !message.is_empty() => message.to_string(),
+            (_, [(_, file)]) => format!("Upload {}", file.path),
+            (_, files) => format!("Upload {} files", files.len()),
+        };
+        let batch = FileBatch {
+            message,
+            branch: upload.branch.clone(),
+            base_commit_id: upload.base_commit_id.clone(),
+            update_workdir: upload.update_workdir,
+            actions,
+        };
+        let commit = self.commit_files(user_id, repo_name, &batch, committer)?;
+
+        Ok(UploadResult {
+            commit,
+            files: uploaded.into_iter().map(|(_, file)| file).collect(),
+        })
+    }
+
+    fn finish_upload(file: PendingFile) -> Result<(Oid, UploadedFile), AppError> {
+        let blob_id = file
+            .writer
+            .commit()
+            .map_err(|e| AppError::InternalServerError(format!("Failed to write blob: {}", e)))?;
+        let uploaded = UploadedFile {
+            path: file.path,
+            blob_id: blob_id.to_string(),
+            size: file.size,
+        };
+        Ok((blob_id, uploaded))
+    }
+
+    // 新文件使用 create；允许覆盖时，分支上已存在的文件改为 update 以保留文件模式
+    fn upload_actions(
+        &self,
+        repo: &Repository,
+        upload: &FileUpload,
+        uploaded: &[(Oid, UploadedFile)],
+    ) -> Result<Vec<FileAction>, AppError> {
+        let (_, ref_name, _) = self.commit_target(repo, upload.branch.as_deref())?;
+        let tip = repo
+            .find_reference(&ref_name)
+            .and_then(|r| r.peel_to_commit())
+            .ok();
+
+        let mut actions = Vec::with_capacity(uploaded.len());
+        for (blob_id, file) in uploaded {
+            let exists = match &tip {
+                Some(tip) if upload.overwrite => entry_at(tip, &file.path)?.is_some(),
+                _ => false,
+            };
+            actions.push(FileAction {
+                action: match exists {
+                    true => FileActionKind::Update,
+                    false => FileActionKind::Create,
+                },
+                path: file.path.clone(),
+                previous_path: None,
+                content: None,
+                blob_id: Some(*blob_id),
+                executable: None,
+            });
+        }
+        Ok(actions)
+    }
+}
@@ -1,18 +1,31 @@
 use std::sync::Arc;
 
-use crate::gitmodule::structs::{CommitDetail, CommitInfo, GitFileEntry, WebSocketMsg};
+use crate::gitmodule::structs::{
+    ArchiveFormat, BlameResult, BranchInfo, CommitDetail, CommitFileChange, CommitFilter,
+    CommitGraphPage, CommitHistoryPage, CompareMode, CompareResult, DiffSettings, FileAction,
+    FileActionKind, FileBatch, FileBatchResult, FileEdit, FileEditResult, FileHistoryEntry,
+    FileUpload, GitTreePage, HistoryOperationResult, MergeFilter, PullResult, PullStrategy,
+    PushResult, RepoStatus, ResetMode, StashDetail, StashEntry, TagInfo, UploadResult,
+    WebSocketMsg, WhitespaceMode,
+};
+use crate::gitmodule::upload::{UPLOAD_CHANNEL_SIZE, UploadEvent};
+use crate::gitmodule::{GitConfig, tree};
 // use crate::gitmodule::{CommitInfo, structs::CommitDetail};
+use crate::models::credential::CredentialKind;
 use crate::services::service;
 use crate::shared::error::AppError;
 use crate::shared::jwt::validate_token;
 use crate::shared::response::ApiResponse;
 use crate::vos::userdata::{MessagePageUserData, UserData};
-use crate::vos::{ReposVo, UserMsg};
+use crate::vos::{CredentialVo, ReposVo, UserMsg};
 use crate::{dtos::request, shared::jwt::Claims};
 use axum::ServiceExt;
-use axum::extract::{Path, Query, WebSocketUpgrade};
+use axum::extract::multipart::MultipartError;
+use axum::extract::{Multipart, Path, Query, WebSocketUpgrade};
+use axum::http::{HeaderMap, header};
 use axum::response::IntoResponse;
 use axum::{Extension, Json, extract::State, http::StatusCode};
+use regex::RegexBuilder;
 use tokio::sync::mpsc;
 use tracing::info;
 use validator::{Validate, ValidationErrors};
@@ -56,7 +69,7 @@ pub async fn get_repo_commit_histories(
     State(service): State<Arc<service::AppState>>,
     // Json(payload): Json<request::RepoRequest>,
     Query(params): Query<request::RepoRequest>,
-) -> Result<ApiResponse<Vec<CommitInfo>>, AppError> {
+) -> Result<ApiResponse<CommitHistoryPage>, AppError> {
     println!("Get repository commit data handler");
 
     let user_id = claims.sub;
@@ -65,11 +78,18 @@ pub async fn get_repo_commit_histories(
     // 获取仓库提交历史
     let limit = params.limit.unwrap_or(10); // 默认获取50条提交记录
     let page = params.page.unwrap_or(1); // 默认第一页
+    if limit == 0 || page == 0 {
+        return Err(AppError::BadRequest(
+            "Page and limit must be greater than 0".into(),
+        ));
+    }
+    let filter = parse_commit_filter(&params)?;
+    let cursor = params.cursor.as_deref().filter(|c| !c.trim().is_empty());
     // let commit_history = service.get_repo_history(&payload.repo_name, limit).await?;
-    if let Some(repo_name) = params.repo_name {
+    if let Some(repo_name) = &params.repo_name {
         let commit_history = service
             .git_service
-            .get_repo_commit_histories(&user_id, &repo_name, limit, page)
+            .get_repo_commit_histories(&user_id, repo_name, &filter, cursor, limit, page)
             .await?;
 
         Ok(ApiResponse::success_data(commit_history))
@@ -78,6 +98,186 @@ pub async fn get_repo_commit_histories(
     }
 }
 
+// 将请求中的过滤参数转换为 CommitFilter，空字符串视为未指定
+fn parse_commit_filter(params: &request::RepoRequest) -> Result<CommitFilter, AppError> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    let message = match non_empty(&params.message) {
+        Some(message) => {
+            let pattern = if params.regex.unwrap_or(false) {
+                message
+            } else {
+                regex::escape(&message)
+            };
+            let regex = RegexBuilder::new(&pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| AppError::BadRequest(format!("Invalid message pattern: {}", e)))?;
+            Some(regex)
+        }
+        None => None,
+    };
+
+    let since = non_empty(&params.since)
+        .map(|v| parse_commit_time(&v, false))
+        .transpose()?;
+    let until = non_empty(&params.until)
+        .map(|v| parse_commit_time(&v, true))
+        .transpose()?;
+
+    let merges = match non_empty(&params.merges) {
+        Some(merges) => MergeFilter::try_from(merges).map_err(AppError::BadRequest)?,
+        None => MergeFilter::default(),
+    };
+
+    Ok(CommitFilter {
+        rev: non_empty(&params.rev),
+        author: non_empty(&params.author),
+        committer: non_empty(&params.committer),
+        message,
+        since,
+        until,
+        path: non_empty(&params.path),
+        merges,
+    })
+}
+
+// 解析时间为 Unix 秒，只有日期时 until 取当天结束
+fn parse_commit_time(value: &str, end_of_day: bool) -> Result<i64, AppError> {
+    if let Ok(seconds) = value.parse::<i64>() {
+        return Ok(seconds);
+    }
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(time.timestamp());
+    }
+    let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| AppError::BadRequest(format!("Invalid date: {}", value)))?;
+    let time = if end_of_day {
+        date.and_hms_opt(23, 59, 59)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    };
+    Ok(time.map(|t| t.and_utc().timestamp()).unwrap_or_default())
+}
+
+// 分支图所需的提交、父提交、列和连线
+#[axum::debug_handler]
+pub async fn get_commit_graph(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::CommitGraphRequest>,
+) -> Result<ApiResponse<CommitGraphPage>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+    let limit = params.limit.unwrap_or(50); // 分支图默认每页 50 个提交
+    let page = params.page.unwrap_or(1);
+    if limit == 0 || page == 0 {
+        return Err(AppError::BadRequest(
+            "Page and limit must be greater than 0".into(),
+        ));
+    }
+    let refs: Vec<String> = params
+        .refs
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(str::to_string)
+        .collect();
+    let cursor = params.cursor.as_deref().filter(|c| !c.trim().is_empty());
+
+    let graph = service
+        .git_service
+        .get_commit_graph(&user_id, repo_name, &refs, cursor, limit, page)
+        .await?;
+
+    Ok(ApiResponse::success_data(graph))
+}
+
+// 指定文件或目录的提交历史，文件会跟随重命名
+#[axum::debug_handler]
+pub async fn get_file_history(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::FileHistoryRequest>,
+) -> Result<ApiResponse<Vec<FileHistoryEntry>>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+    let path = match &params.path {
+        Some(path) if !path.trim().is_empty() => path,
+        _ => return Err(AppError::BadRequest("File path is required".into())),
+    };
+    let limit = params.limit.unwrap_or(10);
+    let page = params.page.unwrap_or(1);
+    if limit == 0 || page == 0 {
+        return Err(AppError::BadRequest(
+            "Page and limit must be greater than 0".into(),
+        ));
+    }
+
+    let history = service
+        .git_service
+        .get_file_history(
+            &user_id,
+            repo_name,
+            path,
+            params.rev.as_deref(),
+            limit,
+            page,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(history))
+}
+
+// 文件每一行最后修改的提交，可限制行范围
+#[axum::debug_handler]
+pub async fn get_file_blame(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::BlameRequest>,
+) -> Result<ApiResponse<BlameResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+    let path = match &params.path {
+        Some(path) if !path.trim().is_empty() => path,
+        _ => return Err(AppError::BadRequest("File path is required".into())),
+    };
+
+    let blame = service
+        .git_service
+        .blame_file(
+            &user_id,
+            repo_name,
+            path,
+            params.rev.as_deref(),
+            params.start_line,
+            params.end_line,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(blame))
+}
+
 #[axum::debug_handler]
 pub async fn get_repos(
     Extension(claims): Extension<Claims>,
@@ -125,13 +325,216 @@ pub async fn clone_repo_for_user(
     Ok(ApiResponse::success("success started!"))
 }
 
+#[axum::debug_handler]
 pub async fn commit_for_user_repo(
     Extension(claims): Extension<Claims>,
     State(service): State<Arc<service::AppState>>,
-    Json(payload): Json<request::CloneRepoRequest>,
-) -> Result<ApiResponse<()>, AppError> {
+    Json(payload): Json<request::CommitRepoRequest>,
+) -> Result<ApiResponse<String>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = match &payload.repo_name {
+        Some(name) if !name.trim().is_empty() => name,
+        _ => return Err(AppError::BadRequest("repo_name is required".into())),
+    };
+
+    let message = match &payload.message {
+        Some(msg) if !msg.trim().is_empty() => msg,
+        _ => return Err(AppError::BadRequest("Commit message is required".into())),
+    };
+
+    let paths: Vec<&str> = payload
+        .paths
+        .as_ref()
+        .map(|paths| paths.iter().map(String::as_str).collect())
+        .unwrap_or_default();
+
+    let author = payload.author.map(|author| GitConfig {
+        name: author.name,
+        email: author.email,
+    });
+
+    let commit_id = service
+        .git_service
+        .commit_changes(
+            &user_id,
+            repo_name,
+            message,
+            &paths,
+            author.as_ref(),
+            &service.ws_manager,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(commit_id))
+}
+
+// 在线编辑单个文件，基于的版本已过期时返回 409 和文件当前内容
+#[axum::debug_handler]
+pub async fn update_file(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::UpdateFileRequest>,
+) -> Result<ApiResponse<FileEditResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let path = match payload.file_path {
+        Some(path) if !path.trim().is_empty() => path,
+        _ => return Err(AppError::BadRequest("file_path is required".into())),
+    };
+    let content = payload
+        .content
+        .ok_or_else(|| AppError::BadRequest("content is required".into()))?;
+    if payload.base_blob_id.is_none() && payload.base_commit_id.is_none() {
+        return Err(AppError::BadRequest(
+            "base_blob_id or base_commit_id is required".into(),
+        ));
+    }
+    let message = match payload.message {
+        Some(msg) if !msg.trim().is_empty() => msg,
+        _ => format!("Update {}", path.trim_matches('/')),
+    };
+
+    let edit = FileEdit {
+        path,
+        content,
+        message,
+        branch: payload.branch_name,
+        base_blob_id: payload.base_blob_id,
+        base_commit_id: payload.base_commit_id,
+    };
+    let result = service
+        .git_service
+        .update_file(&user_id, repo_name, &edit, &service.ws_manager)
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// 一次提交多个文件的新建、修改、删除、移动和权限修改
+#[axum::debug_handler]
+pub async fn commit_files(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::CommitFilesRequest>,
+) -> Result<ApiResponse<FileBatchResult>, AppError> {
     let user_id = claims.sub;
-    todo!()
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let message = match payload.message {
+        Some(msg) if !msg.trim().is_empty() => msg,
+        _ => return Err(AppError::BadRequest("Commit message is required".into())),
+    };
+
+    let mut actions = Vec::new();
+    for action in payload.actions.unwrap_or_default() {
+        let kind = action
+            .action
+            .ok_or_else(|| AppError::BadRequest("action is required".into()))?;
+        let path = action
+            .file_path
+            .ok_or_else(|| AppError::BadRequest("file_path is required".into()))?;
+        actions.push(FileAction {
+            action: FileActionKind::try_from(kind).map_err(AppError::BadRequest)?,
+            path,
+            previous_path: action.previous_path,
+            content: action.content,
+            blob_id: None,
+            executable: action.executable,
+        });
+    }
+    if actions.is_empty() {
+        return Err(AppError::BadRequest("actions is required".into()));
+    }
+
+    let batch = FileBatch {
+        message,
+        branch: payload.branch_name,
+        base_commit_id: payload.base_commit_id,
+        update_workdir: payload.update_workdir.unwrap_or(false),
+        actions,
+    };
+    let result = service
+        .git_service
+        .commit_files(&user_id, repo_name, &batch, &service.ws_manager)
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// 把 multipart 中的文件逐块转发给上传任务，非文件字段忽略
+// 上传任务提前结束（如超出大小限制）时停止读取，错误由上传任务返回
+async fn forward_upload(
+    mut multipart: Multipart,
+    mut tx: futures::channel::mpsc::Sender<UploadEvent>,
+) -> Result<(), AppError> {
+    use futures::SinkExt;
+
+    let to_err = |e: MultipartError| AppError::BadRequest(format!("Invalid upload: {}", e));
+    while let Some(mut field) = multipart.next_field().await.map_err(to_err)? {
+        let Some(name) = field.file_name().filter(|n| !n.is_empty()) else {
+            continue;
+        };
+        if tx.send(UploadEvent::File(name.to_string())).await.is_err() {
+            return Ok(());
+        }
+        while let Some(chunk) = field.chunk().await.map_err(to_err)? {
+            if tx.send(UploadEvent::Chunk(chunk)).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+    let _ = tx.send(UploadEvent::Finish).await;
+    Ok(())
+}
+
+// 上传一个或多个文件到指定目录，作为一次提交写入分支
+#[axum::debug_handler]
+pub async fn upload_files(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::UploadFilesRequest>,
+    multipart: Multipart,
+) -> Result<ApiResponse<UploadResult>, AppError> {
+    let user_id = claims.sub;
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let upload = FileUpload {
+        directory: params.directory.unwrap_or_default(),
+        message: params.message,
+        branch: params.branch_name,
+        base_commit_id: params.base_commit_id,
+        overwrite: params.overwrite.unwrap_or(false),
+        update_workdir: params.update_workdir.unwrap_or(false),
+    };
+
+    let (tx, rx) = futures::channel::mpsc::channel(UPLOAD_CHANNEL_SIZE);
+    let (result, forwarded) = tokio::join!(
+        service
+            .git_service
+            .upload_files(&user_id, repo_name, upload, rx, &service.ws_manager),
+        forward_upload(multipart, tx),
+    );
+    forwarded?;
+    let result = result?;
+
+    info!(
+        "Uploaded {} files to {} at {}",
+        result.files.len(),
+        repo_name,
+        result.commit.commit_id
+    );
+    Ok(ApiResponse::success_data(result))
 }
 
 #[axum::debug_handler]
@@ -165,23 +568,390 @@ pub async fn get_repo_commit_diff(
     //     params.repo_name.unwrap()
     // );
 
+    let settings = DiffSettings::try_from(params.diff)?;
+
     let repo_name = params.repo_name.as_ref().unwrap();
     let commit_id = params.commit_id.as_ref().unwrap();
     let commit_diff_details = service
         .git_service
-        .get_repo_commit_diff(&user_id, repo_name, commit_id)
+        .get_repo_commit_diff(&user_id, repo_name, commit_id, settings)
         .await?;
 
     // todo!()
     Ok(ApiResponse::success_data(commit_diff_details))
 }
 
+// 按需加载单个文件的完整补丁，用于展开被截断的文件
+#[axum::debug_handler]
+pub async fn get_repo_file_diff(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::GetFileDiffRequest>,
+) -> Result<ApiResponse<CommitFileChange>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+    let commit_id = params
+        .commit_id
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Commit ID is required".into()))?;
+    let path = match &params.path {
+        Some(path) if !path.trim().is_empty() => path.trim_matches('/'),
+        _ => return Err(AppError::BadRequest("File path is required".into())),
+    };
+
+    let settings = DiffSettings::try_from(params.diff)?;
+
+    let change = service
+        .git_service
+        .get_commit_file_diff(&user_id, repo_name, commit_id, path, settings)
+        .await?;
+
+    Ok(ApiResponse::success_data(change))
+}
+
+// 工作区状态，列出暂存、未暂存、未跟踪、冲突和被忽略的文件
+#[axum::debug_handler]
+pub async fn get_repo_status(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::RepoStatusRequest>,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+
+    let settings = DiffSettings::try_from(params.diff)?;
+
+    let status = service
+        .git_service
+        .get_status(
+            &user_id,
+            repo_name,
+            params.ignored.unwrap_or(false),
+            settings,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(status))
+}
+
+#[axum::debug_handler]
+pub async fn get_status_file_diff(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::StatusFileDiffRequest>,
+) -> Result<ApiResponse<CommitFileChange>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+    let path = match &params.path {
+        Some(path) if !path.trim().is_empty() => path.trim_matches('/'),
+        _ => return Err(AppError::BadRequest("File path is required".into())),
+    };
+
+    let settings = DiffSettings::try_from(params.diff)?;
+
+    let change = service
+        .git_service
+        .get_status_file_diff(
+            &user_id,
+            repo_name,
+            path,
+            params.staged.unwrap_or(false),
+            settings,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(change))
+}
+
+fn status_paths(payload: &request::StatusPathsRequest) -> Result<(&str, &[String]), AppError> {
+    let repo_name = payload
+        .repo_name
+        .as_deref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    match payload.paths.as_deref() {
+        Some(paths) if !paths.is_empty() => Ok((repo_name, paths)),
+        _ => Err(AppError::BadRequest("paths is required".into())),
+    }
+}
+
+#[axum::debug_handler]
+pub async fn stage_paths(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StatusPathsRequest>,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    let (repo_name, paths) = status_paths(&payload)?;
+    let status = service
+        .git_service
+        .stage_paths(&claims.sub, repo_name, paths)
+        .await?;
+    Ok(ApiResponse::success_data(status))
+}
+
+#[axum::debug_handler]
+pub async fn unstage_paths(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StatusPathsRequest>,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    let (repo_name, paths) = status_paths(&payload)?;
+    let status = service
+        .git_service
+        .unstage_paths(&claims.sub, repo_name, paths)
+        .await?;
+    Ok(ApiResponse::success_data(status))
+}
+
+// 丢弃工作区中未暂存的修改，未跟踪的文件会被删除
+#[axum::debug_handler]
+pub async fn discard_paths(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StatusPathsRequest>,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    let (repo_name, paths) = status_paths(&payload)?;
+    let status = service
+        .git_service
+        .discard_paths(&claims.sub, repo_name, paths)
+        .await?;
+    Ok(ApiResponse::success_data(status))
+}
+
+#[axum::debug_handler]
+pub async fn list_stashes(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::StashListRequest>,
+) -> Result<ApiResponse<Vec<StashEntry>>, AppError> {
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let stashes = service
+        .git_service
+        .list_stashes(&claims.sub, repo_name)
+        .await?;
+
+    Ok(ApiResponse::success_data(stashes))
+}
+
+// 储藏工作区的修改，工作区恢复为 HEAD
+#[axum::debug_handler]
+pub async fn save_stash(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StashSaveRequest>,
+) -> Result<ApiResponse<StashEntry>, AppError> {
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let message = payload
+        .message
+        .as_deref()
+        .map(str::trim)
+        .filter(|m| !m.is_empty());
+
+    let stash = service
+        .git_service
+        .save_stash(
+            &claims.sub,
+            repo_name,
+            message,
+            payload.include_untracked.unwrap_or(false),
+            payload.keep_index.unwrap_or(false),
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(stash))
+}
+
+#[axum::debug_handler]
+pub async fn show_stash(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::StashShowRequest>,
+) -> Result<ApiResponse<StashDetail>, AppError> {
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let settings = DiffSettings::try_from(params.diff)?;
+
+    let detail = service
+        .git_service
+        .show_stash(
+            &claims.sub,
+            repo_name,
+            params.index,
+            params.stash_id.as_deref(),
+            settings,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(detail))
+}
+
+async fn apply_or_pop_stash(
+    claims: Claims,
+    service: Arc<service::AppState>,
+    payload: request::StashApplyRequest,
+    pop: bool,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let status = service
+        .git_service
+        .apply_stash(
+            &claims.sub,
+            repo_name,
+            payload.index,
+            payload.stash_id.as_deref(),
+            payload.restore_index.unwrap_or(false),
+            pop,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(status))
+}
+
+// 应用储藏并保留该储藏
+#[axum::debug_handler]
+pub async fn apply_stash(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StashApplyRequest>,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    apply_or_pop_stash(claims, service, payload, false).await
+}
+
+// 应用储藏，成功后删除该储藏
+#[axum::debug_handler]
+pub async fn pop_stash(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StashApplyRequest>,
+) -> Result<ApiResponse<RepoStatus>, AppError> {
+    apply_or_pop_stash(claims, service, payload, true).await
+}
+
+#[axum::debug_handler]
+pub async fn drop_stash(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::StashApplyRequest>,
+) -> Result<ApiResponse<Vec<StashEntry>>, AppError> {
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let stashes = service
+        .git_service
+        .drop_stash(
+            &claims.sub,
+            repo_name,
+            payload.index,
+            payload.stash_id.as_deref(),
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(stashes))
+}
+
+#[axum::debug_handler]
+pub async fn compare_revisions(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::CompareRequest>,
+) -> Result<ApiResponse<CompareResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+    let base = match &params.base {
+        Some(base) if !base.trim().is_empty() => base,
+        _ => return Err(AppError::BadRequest("base is required".into())),
+    };
+    let head = match &params.head {
+        Some(head) if !head.trim().is_empty() => head,
+        _ => return Err(AppError::BadRequest("head is required".into())),
+    };
+    let mode = match params.mode {
+        Some(mode) => CompareMode::try_from(mode).map_err(AppError::BadRequest)?,
+        None => CompareMode::ThreeDot,
+    };
+
+    let settings = DiffSettings::try_from(params.diff)?;
+
+    let result = service
+        .git_service
+        .compare_revisions(&user_id, repo_name, base, head, mode, settings)
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// 将请求中的差异选项转换为 DiffSettings，未指定的使用默认值
+impl TryFrom<request::DiffQuery> for DiffSettings {
+    type Error = AppError;
+
+    fn try_from(query: request::DiffQuery) -> Result<Self, Self::Error> {
+        let mut settings = DiffSettings::default();
+        if let Some(whitespace) = query.whitespace {
+            settings.whitespace =
+                WhitespaceMode::try_from(whitespace).map_err(AppError::BadRequest)?;
+        }
+        if let Some(similarity) = query.similarity {
+            if similarity > 100 {
+                return Err(AppError::BadRequest(
+                    "similarity must be between 0 and 100".into(),
+                ));
+            }
+            settings.similarity = Some(similarity);
+        }
+        settings.context_lines = query.context_lines;
+        settings.find_renames = query.renames.unwrap_or(settings.find_renames);
+        settings.find_copies = query.copies.unwrap_or(settings.find_copies);
+        settings.pathspec = query
+            .pathspec
+            .map(|paths| {
+                paths
+                    .split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(settings)
+    }
+}
+
 #[axum::debug_handler]
 pub async fn get_repo_files_tree(
     Extension(claims): Extension<Claims>,
     State(service): State<Arc<service::AppState>>,
     Query(params): Query<request::GetRepoFilesRequest>,
-) -> Result<ApiResponse<Vec<GitFileEntry>>, AppError> {
+) -> Result<ApiResponse<GitTreePage>, AppError> {
     let user_id = claims.sub;
 
     // 获取仓库名
@@ -192,18 +962,31 @@ pub async fn get_repo_files_tree(
 
     // 获取可选参数
     let directory_path = params.path.as_deref();
-    let branch = params.branch.as_deref();
+    // rev 优先，兼容旧的 branch 参数
+    let rev = params.rev.as_deref().or(params.branch.as_deref());
+
+    // 默认只列出一层目录，recursive=true 时返回整棵子树
+    let recursive = params.recursive.unwrap_or(false);
+    let cursor = params.cursor.as_deref();
+    let limit = params.limit.unwrap_or(tree::DEFAULT_TREE_PAGE_SIZE);
 
     println!(
-        "Getting files tree for repo {} (path: {:?}, branch: {:?})",
-        repo_name, directory_path, branch
+        "Getting files tree for repo {} (path: {:?}, rev: {:?}, recursive: {})",
+        repo_name, directory_path, rev, recursive
     );
 
     // 调用服务层获取文件树
-    let files = service
-        .git_service
-        .list_repository_files(&user_id, repo_name, directory_path, branch)
-        .await?;
+    let files = if recursive {
+        service
+            .git_service
+            .list_repository_files(&user_id, repo_name, directory_path, rev)
+            .await?
+    } else {
+        service
+            .git_service
+            .list_repository_tree_page(&user_id, repo_name, directory_path, rev, cursor, limit)
+            .await?
+    };
 
     Ok(ApiResponse::success_data(files))
 }
@@ -225,52 +1008,238 @@ pub async fn get_repo_file_content(
         .as_ref()
         .ok_or_else(|| AppError::BadRequest("File path is required".into()))?;
 
-    // 获取可选的分支名
-    let branch = params.branch.as_deref();
+    // 获取可选的版本，rev 优先，兼容旧的 branch 参数
+    let rev = params.rev.as_deref().or(params.branch.as_deref());
+
+    println!(
+        "Getting file content: {}/{} (rev: {:?})",
+        repo_name, file_path, rev
+    );
+
+    // 调用服务获取文件内容
+    let file = service
+        .git_service
+        .get_file_content(&user_id, repo_name, file_path, rev)
+        .await?;
+
+    // 二进制文件返回 {binary: true, size}，内容通过 /repo/raw 下载
+    let Some(content) = file.content else {
+        return Ok(ApiResponse::success_data(file).into_response());
+    };
+
+    // 推断内容类型
+    let content_type = infer_content_type(file_path, content.as_bytes(), false);
+
+    // 直接返回文件内容，而不是封装在ApiResponse中
+    // 这样更适合前端直接处理文本内容
+    Ok((
+        axum::http::StatusCode::OK,
+        [
+            (axum::http::header::CONTENT_TYPE, content_type),
+            (axum::http::header::ETAG, format!("\"{}\"", file.id)),
+        ],
+        content,
+    )
+        .into_response())
+}
+
+// 原始文件下载，按原样返回 blob 字节，支持 ETag 和 Range
+#[axum::debug_handler]
+pub async fn get_repo_file_raw(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<request::GetFileContentRequest>,
+) -> Result<axum::response::Response, AppError> {
+    let user_id = claims.sub;
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+
+    let file_path = params
+        .file_path
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("File path is required".into()))?;
+
+    let rev = params.rev.as_deref().or(params.branch.as_deref());
+
+    // blob 的 ID 即内容的哈希，直接用作 ETag
+    let meta = service
+        .git_service
+        .get_blob_meta(&user_id, repo_name, file_path, rev)
+        .await?;
+    let etag = format!("\"{}\"", meta.id);
+
+    if let Some(value) = headers.get(header::IF_NONE_MATCH) {
+        let matched = value.to_str().unwrap_or("").split(',').any(|tag| {
+            let tag = tag.trim();
+            tag == "*" || tag.trim_start_matches("W/") == etag
+        });
+        if matched {
+            return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+        }
+    }
+
+    let range = match headers.get(header::RANGE) {
+        Some(value) => match parse_range(value.to_str().unwrap_or(""), meta.size) {
+            Ok(range) => range,
+            Err(()) => {
+                return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{}", meta.size))],
+                )
+                    .into_response());
+            }
+        },
+        None => None,
+    };
+
+    let (head, stream) = service
+        .git_service
+        .stream_blob(&user_id, repo_name, &meta.id, range)
+        .await?;
+    let head = head
+        .await
+        .map_err(|_| AppError::InternalServerError("Failed to read blob".into()))?;
+
+    // 同时根据扩展名和文件头识别类型
+    let binary = head.contains(&0);
+    let content_type = infer_content_type(file_path, &head, binary);
+
+    let (status, length) = match range {
+        Some((start, end)) => (StatusCode::PARTIAL_CONTENT, end + 1 - start),
+        None => (StatusCode::OK, meta.size),
+    };
+
+    let mut response = axum::response::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::ETAG, etag)
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Some((start, end)) = range {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, meta.size),
+        );
+    }
+
+    response
+        .body(axum::body::Body::from_stream(stream))
+        .map_err(|e| AppError::InternalServerError(format!("Failed to build response: {}", e)))
+}
+
+// 下载仓库在某个版本的压缩包，边打包边返回
+#[axum::debug_handler]
+pub async fn download_repo_archive(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::ArchiveRepoRequest>,
+) -> Result<axum::response::Response, AppError> {
+    let user_id = claims.sub;
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("Repository name is required".into()))?;
+
+    let format = match params.format {
+        Some(format) => ArchiveFormat::try_from(format).map_err(AppError::BadRequest)?,
+        None => ArchiveFormat::Zip,
+    };
+
+    let (archive, stream) = service
+        .git_service
+        .archive_repository(
+            &user_id,
+            repo_name,
+            params.rev.as_deref(),
+            params.path.as_deref(),
+            format,
+        )
+        .await?;
+
+    info!(
+        "Archiving {} at {} as {}",
+        repo_name, archive.commit_id, archive.file_name
+    );
+
+    axum::response::Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", archive.file_name),
+        )
+        .body(axum::body::Body::from_stream(stream))
+        .map_err(|e| AppError::InternalServerError(format!("Failed to build response: {}", e)))
+}
+
+// 解析单个 Range 请求，返回闭区间 [start, end]
+// 多段或格式错误的 Range 忽略，按完整内容返回；无法满足的范围返回 Err
+fn parse_range(value: &str, size: u64) -> Result<Option<(u64, u64)>, ()> {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+    if spec.contains(',') {
+        return Ok(None);
+    }
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return Ok(None);
+    };
+
+    let parse = |s: &str| s.parse::<u64>().ok();
+    let (start, end) = match (start.trim(), end.trim()) {
+        // bytes=-500 表示最后 500 字节
+        ("", suffix) => match parse(suffix) {
+            Some(0) => return Err(()),
+            Some(suffix) => (size.saturating_sub(suffix), size.saturating_sub(1)),
+            None => return Ok(None),
+        },
+        (start, "") => match parse(start) {
+            Some(start) => (start, size.saturating_sub(1)),
+            None => return Ok(None),
+        },
+        (start, end) => match (parse(start), parse(end)) {
+            (Some(start), Some(end)) if start <= end => (start, end.min(size.saturating_sub(1))),
+            _ => return Ok(None),
+        },
+    };
+
+    if size == 0 || start >= size {
+        return Err(());
+    }
+    Ok(Some((start, end)))
+}
 
-    println!(
-        "Getting file content: {}/{} (branch: {:?})",
-        repo_name, file_path, branch
-    );
+// 根据文件头和扩展名推断内容类型
+fn infer_content_type(file_path: &str, content: &[u8], binary: bool) -> String {
+    // 图片、PDF、压缩包等按文件头识别，文本类的识别结果不可靠，交给扩展名判断
+    if let Some(kind) =
+        infer::get(content).filter(|kind| binary || kind.matcher_type() != infer::MatcherType::Text)
+    {
+        return kind.mime_type().to_string();
+    }
 
-    // 调用服务获取文件内容
-    let content = service
-        .git_service
-        .get_file_content(&user_id, repo_name, file_path, branch)
-        .await?;
-    info!("{}", &content[..10]);
-    // 推断内容类型
-    let content_type = infer_content_type(file_path);
+    let guess = mime_guess::from_path(file_path).first();
+    if binary {
+        return guess
+            .map(|mime| mime.essence_str().to_string())
+            .unwrap_or_else(|| "application/octet-stream".to_string());
+    }
 
-    // 直接返回文件内容，而不是封装在ApiResponse中
-    // 这样更适合前端直接处理文本内容
-    Ok((
-        axum::http::StatusCode::OK,
-        [(axum::http::header::CONTENT_TYPE, content_type)],
-        content,
-    ))
-}
-
-// 根据文件扩展名推断内容类型
-fn infer_content_type(file_path: &str) -> &'static str {
-    if let Some(ext) = file_path.split('.').last() {
-        match ext.to_lowercase().as_str() {
-            "html" => "text/html; charset=utf-8",
-            "css" => "text/css; charset=utf-8",
-            "js" => "application/javascript; charset=utf-8",
-            "json" => "application/json; charset=utf-8",
-            "md" => "text/markdown; charset=utf-8",
-            "rs" => "text/plain; charset=utf-8",   // Rust源码
-            "go" => "text/plain; charset=utf-8",   // Go源码
-            "py" => "text/plain; charset=utf-8",   // Python源码
-            "java" => "text/plain; charset=utf-8", // Java源码
-            "c" | "cpp" | "h" => "text/plain; charset=utf-8", // C/C++源码
-            "txt" => "text/plain; charset=utf-8",
-            // 添加更多文件类型...
-            _ => "text/plain; charset=utf-8", // 默认为纯文本
+    // 文本文件：扩展名对应文本类型时使用它（如 .ts 会被猜成视频），否则按纯文本返回
+    match guess {
+        Some(mime)
+            if mime.type_() == mime_guess::mime::TEXT
+                || matches!(
+                    mime.subtype().as_str(),
+                    "json" | "javascript" | "xml" | "svg"
+                ) =>
+        {
+            format!("{}; charset=utf-8", mime.essence_str())
         }
-    } else {
-        "text/plain; charset=utf-8"
+        _ => "text/plain; charset=utf-8".to_string(),
     }
 }
 
@@ -403,7 +1372,7 @@ pub async fn get_repo_branches(
     Extension(claims): Extension<Claims>,
     State(service): State<Arc<service::AppState>>,
     Query(params): Query<request::GetRepoBranchesRequest>,
-) -> Result<ApiResponse<Vec<String>>, AppError> {
+) -> Result<ApiResponse<Vec<BranchInfo>>, AppError> {
     let user_id = claims.sub;
 
     if let None = params.repo_name {
@@ -412,35 +1381,394 @@ pub async fn get_repo_branches(
 
     let branches = service
         .git_service
-        .get_repo_branches(&user_id, &params.repo_name.unwrap())
+        .get_repo_branches(
+            &user_id,
+            params.repo_name.as_ref().unwrap(),
+            params.branch_name.as_deref(),
+        )
         .await?;
 
     Ok(ApiResponse::success_data(branches))
 }
 
+#[axum::debug_handler]
+pub async fn create_branch(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::CreateBranchRequest>,
+) -> Result<ApiResponse<BranchInfo>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let branch_name = match &payload.branch_name {
+        Some(name) if !name.trim().is_empty() => name.trim(),
+        _ => return Err(AppError::BadRequest("branch_name is required".into())),
+    };
+
+    let branch = service
+        .git_service
+        .create_branch(
+            &user_id,
+            repo_name,
+            branch_name,
+            payload.start_point.as_deref(),
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(branch))
+}
+
+#[axum::debug_handler]
+pub async fn del_branch(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::DelBranchRequest>,
+) -> Result<ApiResponse<()>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let branch_name = payload
+        .branch_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("branch_name is required".into()))?;
+
+    service
+        .git_service
+        .delete_branch(
+            &user_id,
+            repo_name,
+            branch_name,
+            payload.force.unwrap_or(false),
+        )
+        .await?;
+
+    Ok(ApiResponse::success("Branch deleted successfully"))
+}
+
+#[axum::debug_handler]
+pub async fn rename_branch(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::RenameBranchRequest>,
+) -> Result<ApiResponse<BranchInfo>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let branch_name = payload
+        .branch_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("branch_name is required".into()))?;
+    let new_name = match &payload.new_branch_name {
+        Some(name) if !name.trim().is_empty() => name.trim(),
+        _ => return Err(AppError::BadRequest("new_branch_name is required".into())),
+    };
+
+    let branch = service
+        .git_service
+        .rename_branch(&user_id, repo_name, branch_name, new_name)
+        .await?;
+
+    Ok(ApiResponse::success_data(branch))
+}
+
+#[axum::debug_handler]
+pub async fn checkout_branch(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::CheckoutBranchRequest>,
+) -> Result<ApiResponse<BranchInfo>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let branch_name = payload
+        .branch_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("branch_name is required".into()))?;
+
+    let branch = service
+        .git_service
+        .checkout_branch(&user_id, repo_name, branch_name)
+        .await?;
+
+    Ok(ApiResponse::success_data(branch))
+}
+
+#[axum::debug_handler]
+pub async fn get_repo_tags(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Query(params): Query<request::GetRepoTagsRequest>,
+) -> Result<ApiResponse<Vec<TagInfo>>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = params
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let tags = service.git_service.list_tags(&user_id, repo_name).await?;
+
+    Ok(ApiResponse::success_data(tags))
+}
+
+#[axum::debug_handler]
+pub async fn create_tag(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::CreateTagRequest>,
+) -> Result<ApiResponse<()>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let tag_name = match &payload.tag_name {
+        Some(name) if !name.trim().is_empty() => name.trim(),
+        _ => return Err(AppError::BadRequest("tag_name is required".into())),
+    };
+
+    service
+        .git_service
+        .create_tag(
+            &user_id,
+            repo_name,
+            tag_name,
+            payload.target.as_deref(),
+            payload.message.as_deref().filter(|m| !m.trim().is_empty()),
+            payload.release_notes.as_deref(),
+        )
+        .await?;
+
+    Ok(ApiResponse::success("Tag created successfully"))
+}
+
+#[axum::debug_handler]
+pub async fn del_tag(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::DelTagRequest>,
+) -> Result<ApiResponse<()>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let tag_name = payload
+        .tag_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("tag_name is required".into()))?;
+
+    service
+        .git_service
+        .delete_tag(&user_id, repo_name, tag_name)
+        .await?;
+
+    Ok(ApiResponse::success("Tag deleted successfully"))
+}
+
+#[axum::debug_handler]
+pub async fn save_release_notes(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::ReleaseNotesRequest>,
+) -> Result<ApiResponse<()>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let tag_name = payload
+        .tag_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("tag_name is required".into()))?;
+    let notes = payload
+        .notes
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("notes is required".into()))?;
+
+    service
+        .git_service
+        .save_release_notes(&user_id, repo_name, tag_name, notes)
+        .await?;
+
+    Ok(ApiResponse::success("Release notes saved successfully"))
+}
+
 // git pull 拉取更新
 #[axum::debug_handler]
 pub async fn pull_repo(
     Extension(claims): Extension<Claims>,
     State(service): State<Arc<service::AppState>>,
     Json(payload): Json<request::PullRepoRequest>,
-) -> Result<ApiResponse<()>, AppError> {
+) -> Result<ApiResponse<PullResult>, AppError> {
     let user_id = claims.sub;
 
     if let None = payload.repo_name {
         return Err(AppError::BadRequest("repo_name is required".into()));
     }
 
-    service
+    let strategy = match payload.strategy {
+        Some(strategy) => PullStrategy::try_from(strategy).map_err(AppError::BadRequest)?,
+        None => PullStrategy::Merge,
+    };
+
+    let result = service
         .git_service
         .pull_repo(
             &user_id,
             &payload.repo_name.unwrap(),
             payload.branch_name.as_deref(),
+            strategy,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// 生成撤销指定提交的新提交
+#[axum::debug_handler]
+pub async fn revert_commit(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::RevertCommitRequest>,
+) -> Result<ApiResponse<HistoryOperationResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let commit_id = payload
+        .commit_id
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("commit_id is required".into()))?;
+
+    let result = service
+        .git_service
+        .revert_commit(
+            &user_id,
+            repo_name,
+            commit_id,
+            payload.branch_name.as_deref(),
+            payload.mainline,
+            &service.ws_manager,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// 把指定提交的改动应用到分支上
+#[axum::debug_handler]
+pub async fn cherry_pick_commit(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::CherryPickRequest>,
+) -> Result<ApiResponse<HistoryOperationResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let commit_id = payload
+        .commit_id
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("commit_id is required".into()))?;
+
+    let result = service
+        .git_service
+        .cherry_pick_commit(
+            &user_id,
+            repo_name,
+            commit_id,
+            payload.branch_name.as_deref(),
+            payload.mainline,
+            &service.ws_manager,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// 把分支重置到指定提交
+#[axum::debug_handler]
+pub async fn reset_branch(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::ResetBranchRequest>,
+) -> Result<ApiResponse<HistoryOperationResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+    let commit_id = payload
+        .commit_id
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("commit_id is required".into()))?;
+    let mode = match payload.mode {
+        Some(mode) => ResetMode::try_from(mode).map_err(AppError::BadRequest)?,
+        None => ResetMode::Mixed,
+    };
+
+    let result = service
+        .git_service
+        .reset_branch(
+            &user_id,
+            repo_name,
+            commit_id,
+            payload.branch_name.as_deref(),
+            mode,
+            &service.ws_manager,
+        )
+        .await?;
+
+    Ok(ApiResponse::success_data(result))
+}
+
+// git push 推送本地提交
+#[axum::debug_handler]
+pub async fn push_repo(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::PushRepoRequest>,
+) -> Result<ApiResponse<PushResult>, AppError> {
+    let user_id = claims.sub;
+
+    let repo_name = payload
+        .repo_name
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("repo_name is required".into()))?;
+
+    let result = service
+        .git_service
+        .push_repo(
+            &user_id,
+            repo_name,
+            payload.remote_name.as_deref(),
+            payload.branch_name.as_deref(),
+            &service.ws_manager,
         )
         .await?;
 
-    Ok(ApiResponse::success("Repository pulled successfully"))
+    Ok(ApiResponse::success_data(result))
 }
 
 #[axum::debug_handler]
@@ -536,7 +1864,7 @@ pub async fn get_commit_counts(
     if let Some(repo_name) = &params.repo_name {
         let commit_counts = service
             .git_service
-            .get_repo_commit_count(&user_id, repo_name)
+            .get_repo_commit_count(&user_id, repo_name, params.rev.as_deref())
             // .get_repo_commit_counts(&user_id, &repo_name)
             .await?;
 
@@ -555,3 +1883,74 @@ pub async fn get_user_list(
 
     Ok(ApiResponse::success_data(user_list))
 }
+
+#[axum::debug_handler]
+pub async fn save_credential(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::SaveCredentialRequest>,
+) -> Result<ApiResponse<()>, AppError> {
+    let user_id = claims.sub;
+
+    let host = match &payload.host {
+        Some(host) if !host.trim().is_empty() => host.trim(),
+        _ => return Err(AppError::BadRequest("host is required".into())),
+    };
+
+    let kind = payload
+        .kind
+        .clone()
+        .ok_or_else(|| AppError::BadRequest("kind is required".into()))
+        .and_then(|kind| CredentialKind::try_from(kind).map_err(AppError::BadRequest))?;
+
+    let secret = match &payload.secret {
+        Some(secret) if !secret.is_empty() => secret,
+        _ => return Err(AppError::BadRequest("secret is required".into())),
+    };
+
+    service
+        .git_service
+        .save_credential(
+            &user_id,
+            host,
+            kind,
+            payload.username.as_deref(),
+            secret,
+            payload.passphrase.as_deref(),
+        )
+        .await?;
+
+    Ok(ApiResponse::success("Credential saved successfully"))
+}
+
+#[axum::debug_handler]
+pub async fn get_credentials(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+) -> Result<ApiResponse<Vec<CredentialVo>>, AppError> {
+    let user_id = claims.sub;
+    let credentials = service.git_service.list_credentials(&user_id).await?;
+
+    Ok(ApiResponse::success_data(credentials))
+}
+
+#[axum::debug_handler]
+pub async fn del_credential(
+    Extension(claims): Extension<Claims>,
+    State(service): State<Arc<service::AppState>>,
+    Json(payload): Json<request::DelCredentialRequest>,
+) -> Result<ApiResponse<()>, AppError> {
+    let user_id = claims.sub;
+
+    let host = payload
+        .host
+        .as_ref()
+        .ok_or_else(|| AppError::BadRequest("host is required".into()))?;
+
+    service
+        .git_service
+        .delete_credential(&user_id, host.trim())
+        .await?;
+
+    Ok(ApiResponse::success("Credential deleted successfully"))
+}
@@ -1,5 +1,6 @@
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     http::{HeaderValue, Method},
     middleware,
     routing::{get, post},
@@ -59,19 +60,59 @@ pub async fn create_router() -> Result<Router, AppError> {
                         .route("/user/update", post(handler::update_user_data))
                         .route("/user/change_password", post(handler::update_user_password))
                         .route("/user/msg", get(handler::get_user_messages))
+                        .route("/credential/list", get(handler::get_credentials))
+                        .route("/credential/save", post(handler::save_credential))
+                        .route("/credential/del", post(handler::del_credential))
                         .route(
                             "/repo/commithistories",
                             get(handler::get_repo_commit_histories),
                         )
+                        .route("/repo/graph", get(handler::get_commit_graph))
+                        .route("/repo/file/history", get(handler::get_file_history))
+                        .route("/repo/file/blame", get(handler::get_file_blame))
                         .route("/repo/repos", get(handler::get_repos))
                         .route("/repo/clone", post(handler::clone_repo_for_user))
                         .route("/repo/files", get(handler::get_repo_files_tree))
                         .route("/repo/filecontent", get(handler::get_repo_file_content))
+                        .route("/repo/raw", get(handler::get_repo_file_raw))
+                        .route("/repo/archive", get(handler::download_repo_archive))
                         .route("/repo/getdiff", get(handler::get_repo_commit_diff))
+                        .route("/repo/diff/file", get(handler::get_repo_file_diff))
+                        .route("/repo/compare", get(handler::compare_revisions))
                         .route("/repo/update", post(handler::update_repo_data))
                         .route("/repo/del", post(handler::del_repo_for_user))
                         .route("/repo/branches", get(handler::get_repo_branches))
+                        .route("/repo/branch/create", post(handler::create_branch))
+                        .route("/repo/branch/del", post(handler::del_branch))
+                        .route("/repo/branch/rename", post(handler::rename_branch))
+                        .route("/repo/branch/checkout", post(handler::checkout_branch))
+                        .route("/repo/tags", get(handler::get_repo_tags))
+                        .route("/repo/tag/create", post(handler::create_tag))
+                        .route("/repo/tag/del", post(handler::del_tag))
+                        .route("/repo/tag/release", post(handler::save_release_notes))
                         .route("/repo/pull", post(handler::pull_repo))
+                        .route("/repo/push", post(handler::push_repo))
+                        .route("/repo/status", get(handler::get_repo_status))
+                        .route("/repo/status/diff", get(handler::get_status_file_diff))
+                        .route("/repo/stage", post(handler::stage_paths))
+                        .route("/repo/unstage", post(handler::unstage_paths))
+                        .route("/repo/discard", post(handler::discard_paths))
+                        .route("/repo/stashes", get(handler::list_stashes))
+                        .route("/repo/stash/save", post(handler::save_stash))
+                        .route("/repo/stash/show", get(handler::show_stash))
+                        .route("/repo/stash/apply", post(handler::apply_stash))
+                        .route("/repo/stash/pop", post(handler::pop_stash))
+                        .route("/repo/stash/drop", post(handler::drop_stash))
+                        .route("/repo/commit", post(handler::commit_for_user_repo))
+                        .route("/repo/commit/files", post(handler::commit_files))
+                        .route(
+                            "/repo/upload",
+                            post(handler::upload_files).layer(DefaultBodyLimit::disable()),
+                        )
+                        .route("/repo/file/update", post(handler::update_file))
+                        .route("/repo/revert", post(handler::revert_commit))
+                        .route("/repo/cherry_pick", post(handler::cherry_pick_commit))
+                        .route("/repo/reset", post(handler::reset_branch))
                         .route("/repo/commit_count", get(handler::get_commit_counts))
                         .layer(middleware::from_fn(auth_middleware::auth_middleware)),
                 ),
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 凭据类型
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialKind {
+    HttpsToken,
+    SshKey,
+}
+
+impl std::fmt::Display for CredentialKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialKind::HttpsToken => write!(f, "https_token"),
+            CredentialKind::SshKey => write!(f, "ssh_key"),
+        }
+    }
+}
+
+impl TryFrom<String> for CredentialKind {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        match s.to_lowercase().as_str() {
+            "https_token" => Ok(CredentialKind::HttpsToken),
+            "ssh_key" => Ok(CredentialKind::SshKey),
+            _ => Err(format!("Invalid credential kind: {}", s)),
+        }
+    }
+}
+
+/// 用户的远程仓库凭据，对应 git_credentials 表
+/// secret 和 passphrase 均为加密后的内容，不能返回给前端
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct GitCredential {
+    pub id: i32,
+    pub username: String,
+    pub host: String,
+    #[sqlx(try_from = "String")]
+    pub kind: CredentialKind,
+    pub auth_username: Option<String>,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    #[serde(skip_serializing)]
+    pub passphrase: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
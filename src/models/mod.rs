@@ -1,2 +1,4 @@
+pub mod credential;
 pub mod message;
+pub mod release;
 pub mod user;
@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// 标签对应的发布说明，对应 repo_releases 表
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Release {
+    pub id: i32,
+    pub username: String,
+    pub repo_name: String,
+    pub tag_name: String,
+    pub notes: String,
+    pub updated_at: DateTime<Utc>,
+}
@@ -1,22 +1,42 @@
 use std::sync::Arc;
 
 use futures::TryFutureExt;
+use futures::channel::oneshot;
 use r2d2::Pool;
 use redis::{Client, Commands};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use sqlx::{PgPool, Row, query, query_as};
 use tracing::info;
 
 use crate::db::pg::PostgrePool;
 use crate::dtos::request::{self, RegisterRequest, UserUpdateRequest};
-use crate::gitmodule::structs::{CommitDetail, CommitInfo, GitFileEntry, WebSocketManager};
-use crate::gitmodule::{GitManager, structs};
+use crate::gitmodule::archive::ArchiveStream;
+use crate::gitmodule::blob::BlobStream;
+use crate::gitmodule::credential::{self, RemoteCredential};
+use crate::gitmodule::structs::{
+    ArchiveFormat, ArchiveInfo, BlameResult, BlobMeta, BranchInfo, CommitDetail, CommitFileChange,
+    CommitFilter, CommitGraphPage, CommitHistoryPage, CompareMode, CompareResult, DiffLimits,
+    DiffSettings, FileBatch, FileBatchResult, FileContent, FileEdit, FileEditResult,
+    FileHistoryEntry, FileUpload, GitTreePage, HistoryOperationResult, PullResult, PullStrategy,
+    PushResult, RepoStatus, ResetMode, StashDetail, StashEntry, TagInfo, UploadLimits,
+    UploadResult, WebSocketManager,
+};
+use crate::gitmodule::upload::UploadStream;
+use crate::gitmodule::{GitConfig, GitManager, blame, history, structs, tree};
+use crate::models::credential::CredentialKind;
 use crate::models::message::{Message, MessageCreate, MessageType};
 use crate::models::user::User;
 use crate::models::{self, message};
 use crate::shared::error::AppError;
-use crate::shared::{jwt, setting};
-use crate::vos::ReposVo;
+use crate::shared::{crypto, jwt, setting};
 use crate::vos::userdata::{MessagePageUserData, UserData};
+use crate::vos::{CredentialVo, ReposVo};
+
+// blame 缓存保留 7 天
+const BLAME_CACHE_TTL: u64 = 7 * 24 * 3600;
+// 提交数缓存保留 1 天，ref 移动后旧的键不再使用，到期自动清除
+const COMMIT_COUNT_CACHE_TTL: u64 = 24 * 3600;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -29,8 +49,9 @@ pub struct AppState {
 impl AppState {
     pub async fn init_app() -> Result<Arc<AppState>, AppError> {
         let pg_db = PostgrePool::new("postgresql://postgres:@localhost:5432/mydb").await;
+        pg_db.create_tables().await?;
         let redis = RedisPool::new();
-        let git_service = GitService::new();
+        let git_service = GitService::new(pg_db.clone());
         let ws_manager = WebSocketManager::new();
 
         Ok(Arc::new(AppState {
@@ -564,10 +585,11 @@ impl RedisPool {
 pub struct GitService {
     git_manager: GitManager,
     pool: Pool<Client>,
+    pg_db: PostgrePool,
 }
 
 impl GitService {
-    pub fn new() -> Self {
+    pub fn new(pg_db: PostgrePool) -> Self {
         let redis = Client::open("redis://127.0.0.1:6379").expect("Failed to connect to Redis");
         let pool = r2d2::Pool::builder().build(redis).unwrap();
         let setting = setting::get_config();
@@ -577,7 +599,11 @@ impl GitService {
             std::str::from_utf8(base_path).expect("Invalid UTF-8 sequence in base path");
         let git_manager = GitManager::new(base_path_str);
 
-        Self { git_manager, pool }
+        Self {
+            git_manager,
+            pool,
+            pg_db,
+        }
     }
 
     pub async fn generate_repopath(&self, user_name: &str) -> Result<(), AppError> {
@@ -608,54 +634,262 @@ impl GitService {
         repo_name: &str,
         ws_manager: &WebSocketManager,
     ) -> Result<String, AppError> {
+        let credential = self.load_credential(user_id, repo_url).await?;
         self.git_manager
-            .clone_repository_for_user(user_id, repo_url, repo_name, ws_manager)
+            .clone_repository_for_user(user_id, repo_url, repo_name, credential, ws_manager)
             .await
     }
 
-    // 用户提交更改的方法
-    pub async fn commit_changes(
+    // 根据远端地址的主机名查找并解密用户保存的凭据
+    async fn load_credential(
+        &self,
+        user_id: &str,
+        remote_url: &str,
+    ) -> Result<Option<RemoteCredential>, AppError> {
+        let Some(host) = credential::remote_host(remote_url) else {
+            return Ok(None);
+        };
+        let Some(stored) = self.pg_db.get_credential(user_id, &host).await? else {
+            return Ok(None);
+        };
+
+        let secret = crypto::decrypt_secret(&stored.secret)?;
+        let credential = match stored.kind {
+            CredentialKind::HttpsToken => RemoteCredential::HttpsToken {
+                username: stored.auth_username,
+                token: secret,
+            },
+            CredentialKind::SshKey => RemoteCredential::SshKey {
+                username: stored.auth_username,
+                private_key: secret,
+                passphrase: stored
+                    .passphrase
+                    .as_deref()
+                    .map(crypto::decrypt_secret)
+                    .transpose()?,
+            },
+        };
+
+        Ok(Some(credential))
+    }
+
+    async fn load_remote_credential(
         &self,
         user_id: &str,
         repo_name: &str,
-        message: &str,
-        paths: &[&str],
-        // user_data: &UserData, // 从认证服务获取的用户数据
-    ) -> Result<String, AppError> {
+        remote_name: &str,
+    ) -> Result<Option<RemoteCredential>, AppError> {
+        let remote_url = self
+            .git_manager
+            .get_remote_url(user_id, repo_name, remote_name)?;
+        self.load_credential(user_id, &remote_url).await
+    }
+
+    pub async fn save_credential(
+        &self,
+        user_id: &str,
+        host: &str,
+        kind: CredentialKind,
+        auth_username: Option<&str>,
+        secret: &str,
+        passphrase: Option<&str>,
+    ) -> Result<(), AppError> {
+        let secret = crypto::encrypt_secret(secret)?;
+        let passphrase = passphrase.map(crypto::encrypt_secret).transpose()?;
+
+        self.pg_db
+            .save_credential(
+                user_id,
+                &host.to_lowercase(),
+                &kind,
+                auth_username,
+                &secret,
+                passphrase.as_deref(),
+            )
+            .await
+    }
+
+    pub async fn list_credentials(&self, user_id: &str) -> Result<Vec<CredentialVo>, AppError> {
+        let credentials = self.pg_db.list_credentials(user_id).await?;
+
+        // 只返回元数据，不返回任何密钥内容
+        Ok(credentials
+            .into_iter()
+            .map(|c| CredentialVo {
+                host: c.host,
+                kind: c.kind,
+                auth_username: c.auth_username,
+                created_at: c.created_at,
+            })
+            .collect())
+    }
+
+    pub async fn delete_credential(&self, user_id: &str, host: &str) -> Result<(), AppError> {
+        self.pg_db
+            .delete_credential(user_id, &host.to_lowercase())
+            .await
+    }
+
+    // 获取用户的 git 身份，先查 redis，缓存过期时再查数据库
+    async fn get_user_identity(&self, user_id: &str) -> Result<GitConfig, AppError> {
         let mut conn = self
             .pool
             .get()
             .map_err(|_| AppError::InternalServerError("Redis connect failed".to_string()))?;
 
-        // 查找用户
-        let user_json: String = conn
+        let cached: Option<String> = conn
             .get(format!("user:{}", user_id))
-            .map_err(|_| AppError::NotFound("User not found".to_string()))?;
+            .map_err(|_| AppError::InternalServerError("Redis operation failed".to_string()))?;
 
-        let user: User = serde_json::from_str(&user_json)
-            .map_err(|_| AppError::InternalServerError("Failed to parse user data".to_string()))?;
-        // let email = user.email;
+        let user: User = match cached {
+            Some(user_json) => serde_json::from_str(&user_json).map_err(|_| {
+                AppError::InternalServerError("Failed to parse user data".to_string())
+            })?,
+            None => {
+                let user = self.pg_db.get_user(user_id).await?;
+                // 回填缓存，失败不影响提交
+                if let Ok(user_json) = serde_json::to_string(&user) {
+                    let cached: Result<(), _> =
+                        conn.set_ex(format!("user:{}", user.username), user_json, 3600);
+                    if cached.is_err() {
+                        info!("Failed to cache user to Redis: user is {}", user.username);
+                    }
+                }
+                user
+            }
+        };
 
-        self.git_manager.commit_for_user(
-            user_id,
-            repo_name,
-            message,
-            paths,
-            // &user_data.username,
-            // &user_data.email,
-            &user.email,
-        )
+        Ok(GitConfig {
+            name: user.username,
+            email: user.email,
+        })
     }
 
+    // 用户提交更改的方法
+    pub async fn commit_changes(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        message: &str,
+        paths: &[&str],
+        author: Option<&GitConfig>,
+        ws_manager: &WebSocketManager,
+    ) -> Result<String, AppError> {
+        let committer = self.get_user_identity(user_id).await?;
+
+        let commit_id = self
+            .git_manager
+            .commit_for_user(user_id, repo_name, message, paths, &committer, author)?;
+
+        // websocket 通知前端
+        ws_manager
+            .send_repo_event(user_id, repo_name, &format!("COMMITTED:{}", commit_id))
+            .await;
+
+        Ok(commit_id)
+    }
+
+    // 在线编辑单个文件，提交成功后与普通提交一样通知前端
+    pub async fn update_file(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        edit: &FileEdit,
+        ws_manager: &WebSocketManager,
+    ) -> Result<FileEditResult, AppError> {
+        let committer = self.get_user_identity(user_id).await?;
+
+        let result = self
+            .git_manager
+            .update_file(user_id, repo_name, edit, &committer)?;
+
+        ws_manager
+            .send_repo_event(
+                user_id,
+                repo_name,
+                &format!("COMMITTED:{}", result.commit_id),
+            )
+            .await;
+
+        Ok(result)
+    }
+
+    // 一次提交多个文件的修改
+    pub async fn commit_files(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        batch: &FileBatch,
+        ws_manager: &WebSocketManager,
+    ) -> Result<FileBatchResult, AppError> {
+        let committer = self.get_user_identity(user_id).await?;
+
+        let result = self
+            .git_manager
+            .commit_files(user_id, repo_name, batch, &committer)?;
+
+        ws_manager
+            .send_repo_event(
+                user_id,
+                repo_name,
+                &format!("COMMITTED:{}", result.commit_id),
+            )
+            .await;
+
+        Ok(result)
+    }
+
+    // 上传的内容由 files 逐块送入，接收和写入对象库在阻塞线程中进行
+    pub async fn upload_files(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        upload: FileUpload,
+        files: UploadStream,
+        ws_manager: &WebSocketManager,
+    ) -> Result<UploadResult, AppError> {
+        let committer = self.get_user_identity(user_id).await?;
+        let limits = Self::upload_limits();
+
+        let git_manager = self.git_manager.clone();
+        let (user, repo) = (user_id.to_string(), repo_name.to_string());
+        let result = tokio::task::spawn_blocking(move || {
+            git_manager.upload_files(&user, &repo, &upload, &limits, files, &committer)
+        })
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Upload task failed: {}", e)))??;
+
+        ws_manager
+            .send_repo_event(
+                user_id,
+                repo_name,
+                &format!("COMMITTED:{}", result.commit.commit_id),
+            )
+            .await;
+
+        Ok(result)
+    }
+
+    // 提交数按 ref 指向的提交缓存在 Redis 中，ref 移动后会使用新的键
     pub async fn get_repo_commit_count(
         &self,
         user_id: &str,
         repo_name: &str,
+        rev: Option<&str>,
     ) -> Result<usize, AppError> {
         //先从redis里面找，再去数据库里面找。都没有再去git_manager里面找
+        let commit_id = self
+            .git_manager
+            .resolve_commit_id(user_id, repo_name, rev)?;
+        let cache_key = format!("commit_count:{}", commit_id);
+        if let Some(total_count) = self.get_cached(&cache_key) {
+            return Ok(total_count);
+        }
+
         let total_count = self
             .git_manager
-            .get_total_commits_count(user_id, repo_name)?;
+            .get_total_commits_count(user_id, repo_name, &commit_id)?;
+        self.set_cached(&cache_key, &total_count, COMMIT_COUNT_CACHE_TTL);
         Ok(total_count)
     }
 
@@ -663,47 +897,337 @@ impl GitService {
         &self,
         user_id: &str,
         repo_name: &str,
+        filter: &CommitFilter,
+        cursor: Option<&str>,
         limit: usize,
         page: usize,
-    ) -> Result<Vec<CommitInfo>, AppError> {
-        let total_count = self.get_repo_commit_count(user_id, repo_name).await?;
-        info!("get count ={}", total_count);
-        if total_count == 0 {
-            return Ok(vec![]);
-        }
-        if (page - 1) * limit > total_count {
-            return Err(AppError::BadRequest(
-                "Page number exceeds total commit count".to_string(),
-            ));
+    ) -> Result<CommitHistoryPage, AppError> {
+        // 按页码访问且没有过滤条件时可以用总提交数提前判断页码
+        if cursor.is_none() && filter.is_empty() {
+            let total_count = self.get_repo_commit_count(user_id, repo_name, None).await?;
+            info!("get count ={}", total_count);
+            if history::page_offset(page, limit)? > total_count {
+                return Err(AppError::BadRequest(
+                    "Page number exceeds total commit count".to_string(),
+                ));
+            }
         }
         self.git_manager
-            .get_commit_histories(user_id, repo_name, limit, page, total_count)
+            .get_commit_histories(user_id, repo_name, filter, cursor, limit, page)
+    }
+
+    pub async fn get_commit_graph(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        refs: &[String],
+        cursor: Option<&str>,
+        limit: usize,
+        page: usize,
+    ) -> Result<CommitGraphPage, AppError> {
+        self.git_manager
+            .get_commit_graph(user_id, repo_name, refs, cursor, limit, page)
+    }
+
+    pub async fn get_file_history(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        path: &str,
+        rev: Option<&str>,
+        limit: usize,
+        page: usize,
+    ) -> Result<Vec<FileHistoryEntry>, AppError> {
+        self.git_manager
+            .get_file_history(user_id, repo_name, path, rev, limit, page)
+    }
+
+    // blame 结果按 (blob, 提交) 缓存在 Redis 中，同一版本的结果不会变化
+    pub async fn blame_file(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        file_path: &str,
+        rev: Option<&str>,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> Result<BlameResult, AppError> {
+        let (commit_id, blob_id) = self
+            .git_manager
+            .get_blame_target(user_id, repo_name, file_path, rev)?;
+        let cache_key = format!(
+            "blame:{}:{}:{}",
+            blob_id,
+            commit_id,
+            file_path.trim_matches('/')
+        );
+
+        let blame = match self.get_cached(&cache_key) {
+            Some(blame) => blame,
+            None => {
+                let git_manager = self.git_manager.clone();
+                let (user_id, repo_name, file_path, commit_id) = (
+                    user_id.to_string(),
+                    repo_name.to_string(),
+                    file_path.to_string(),
+                    commit_id.clone(),
+                );
+                let blame = tokio::task::spawn_blocking(move || {
+                    git_manager.blame_file(&user_id, &repo_name, &file_path, &commit_id)
+                })
+                .await
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Blame task failed: {}", e))
+                })??;
+                self.set_cached(&cache_key, &blame, BLAME_CACHE_TTL);
+                blame
+            }
+        };
+
+        blame::select_blame_lines(blame, start_line, end_line)
+    }
+
+    // 缓存读写失败只记录日志，不影响请求
+    fn get_cached<T: DeserializeOwned>(&self, cache_key: &str) -> Option<T> {
+        let mut conn = self.pool.get().ok()?;
+        let cached: Option<String> = conn.get(cache_key).ok()?;
+        serde_json::from_str(&cached?).ok()
+    }
+
+    fn set_cached<T: Serialize>(&self, cache_key: &str, value: &T, ttl: u64) {
+        let result = self
+            .pool
+            .get()
+            .map_err(|e| e.to_string())
+            .and_then(|mut conn| {
+                let value_json = serde_json::to_string(value).map_err(|e| e.to_string())?;
+                conn.set_ex::<_, _, ()>(cache_key, value_json, ttl)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            info!("Failed to cache {}: {}", cache_key, e);
+        }
     }
 
     pub async fn get_repos_data_for_users(&self, user_id: &str) -> Result<Vec<ReposVo>, AppError> {
         self.git_manager.get_repos_data_for_users(user_id)
     }
 
+    // 按配置文件设置大差异保护
+    fn diff_limits() -> DiffLimits {
+        let setting = setting::get_config();
+        DiffLimits {
+            max_files: setting.diff.max_files,
+            max_lines_per_file: setting.diff.max_lines_per_file,
+            max_bytes_per_file: setting.diff.max_bytes_per_file,
+        }
+    }
+
+    // 按配置文件设置上传大小限制
+    fn upload_limits() -> UploadLimits {
+        let setting = setting::get_config();
+        UploadLimits {
+            max_file_size: setting.upload.max_file_size,
+            max_total_size: setting.upload.max_total_size,
+        }
+    }
+
     pub async fn get_repo_commit_diff(
         &self,
         user_id: &str,
         repo_name: &str,
         commit_id: &str,
+        mut settings: DiffSettings,
     ) -> Result<CommitDetail, AppError> {
+        settings.limits = Self::diff_limits();
+        self.git_manager
+            .get_commit_detail(user_id, repo_name, commit_id, &settings)
+            .await
+    }
+
+    pub async fn get_status(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        include_ignored: bool,
+        mut settings: DiffSettings,
+    ) -> Result<RepoStatus, AppError> {
+        settings.limits = Self::diff_limits();
+        self.git_manager
+            .get_status(user_id, repo_name, include_ignored, &settings)
+    }
+
+    pub async fn list_stashes(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+    ) -> Result<Vec<StashEntry>, AppError> {
+        self.git_manager.list_stashes(user_id, repo_name)
+    }
+
+    pub async fn save_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        message: Option<&str>,
+        include_untracked: bool,
+        keep_index: bool,
+    ) -> Result<StashEntry, AppError> {
+        let stasher = self.get_user_identity(user_id).await?;
+        self.git_manager.save_stash(
+            user_id,
+            repo_name,
+            message,
+            include_untracked,
+            keep_index,
+            &stasher,
+        )
+    }
+
+    pub async fn show_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+        mut settings: DiffSettings,
+    ) -> Result<StashDetail, AppError> {
+        settings.limits = Self::diff_limits();
+        self.git_manager
+            .show_stash(user_id, repo_name, index, stash_id, &settings)
+    }
+
+    // 应用或弹出储藏后返回最新的工作区状态
+    pub async fn apply_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+        restore_index: bool,
+        pop: bool,
+    ) -> Result<RepoStatus, AppError> {
+        self.git_manager
+            .apply_stash(user_id, repo_name, index, stash_id, restore_index, pop)?;
+        self.get_status(user_id, repo_name, false, DiffSettings::default())
+            .await
+    }
+
+    pub async fn drop_stash(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        index: Option<usize>,
+        stash_id: Option<&str>,
+    ) -> Result<Vec<StashEntry>, AppError> {
+        self.git_manager
+            .drop_stash(user_id, repo_name, index, stash_id)
+    }
+
+    // 单个文件暂存或未暂存的完整补丁，不受大差异限制
+    pub async fn get_status_file_diff(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        path: &str,
+        staged: bool,
+        settings: DiffSettings,
+    ) -> Result<CommitFileChange, AppError> {
         self.git_manager
-            .get_commit_detail(user_id, repo_name, commit_id)
+            .get_status_file_diff(user_id, repo_name, path, staged, &settings)
+    }
+
+    // 暂存、取消暂存、丢弃修改后都返回最新的状态
+    pub async fn stage_paths(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        paths: &[String],
+    ) -> Result<RepoStatus, AppError> {
+        self.git_manager.stage_paths(user_id, repo_name, paths)?;
+        self.get_status(user_id, repo_name, false, DiffSettings::default())
+            .await
+    }
+
+    pub async fn unstage_paths(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        paths: &[String],
+    ) -> Result<RepoStatus, AppError> {
+        self.git_manager.unstage_paths(user_id, repo_name, paths)?;
+        self.get_status(user_id, repo_name, false, DiffSettings::default())
+            .await
+    }
+
+    pub async fn discard_paths(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        paths: &[String],
+    ) -> Result<RepoStatus, AppError> {
+        self.git_manager.discard_paths(user_id, repo_name, paths)?;
+        self.get_status(user_id, repo_name, false, DiffSettings::default())
             .await
     }
 
+    // 单个文件的完整补丁，不受大差异限制
+    pub async fn get_commit_file_diff(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        path: &str,
+        settings: DiffSettings,
+    ) -> Result<CommitFileChange, AppError> {
+        self.git_manager
+            .get_commit_file_diff(user_id, repo_name, commit_id, path, &settings)
+    }
+
+    pub async fn compare_revisions(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        base: &str,
+        head: &str,
+        mode: CompareMode,
+        mut settings: DiffSettings,
+    ) -> Result<CompareResult, AppError> {
+        settings.limits = Self::diff_limits();
+        self.git_manager
+            .compare_revisions(user_id, repo_name, base, head, mode, &settings)
+    }
+
+    // 递归返回整棵子树，不分页
     pub async fn list_repository_files(
         &self,
         user_id: &str,
         repo_name: &str,
         directory_path: Option<&str>,
-        branch: Option<&str>,
-    ) -> Result<Vec<GitFileEntry>, AppError> {
+        rev: Option<&str>,
+    ) -> Result<GitTreePage, AppError> {
+        let entries =
+            self.git_manager
+                .list_repository_files(user_id, repo_name, directory_path, rev)?;
+        Ok(GitTreePage {
+            path: tree::normalize_tree_path(directory_path.unwrap_or("")),
+            total: entries.len(),
+            entries,
+            next_cursor: None,
+        })
+    }
+
+    pub async fn list_repository_tree_page(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        directory_path: Option<&str>,
+        rev: Option<&str>,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<GitTreePage, AppError> {
         self.git_manager
-            .list_repository_files(user_id, repo_name, directory_path, branch)
+            .list_tree_page(user_id, repo_name, directory_path, rev, cursor, limit)
     }
 
     pub async fn get_file_content(
@@ -711,10 +1235,44 @@ impl GitService {
         user_id: &str,
         repo_name: &str,
         file_path: &str,
-        branch: Option<&str>,
-    ) -> Result<String, AppError> {
+        rev: Option<&str>,
+    ) -> Result<FileContent, AppError> {
         self.git_manager
-            .get_file_content(user_id, repo_name, file_path, branch)
+            .get_file_content(user_id, repo_name, file_path, rev)
+    }
+
+    pub async fn get_blob_meta(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        file_path: &str,
+        rev: Option<&str>,
+    ) -> Result<BlobMeta, AppError> {
+        self.git_manager
+            .get_blob_meta(user_id, repo_name, file_path, rev)
+    }
+
+    pub async fn stream_blob(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        blob_id: &str,
+        range: Option<(u64, u64)>,
+    ) -> Result<(oneshot::Receiver<Vec<u8>>, BlobStream), AppError> {
+        self.git_manager
+            .stream_blob(user_id, repo_name, blob_id, range)
+    }
+
+    pub async fn archive_repository(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        rev: Option<&str>,
+        directory_path: Option<&str>,
+        format: ArchiveFormat,
+    ) -> Result<(ArchiveInfo, ArchiveStream), AppError> {
+        self.git_manager
+            .archive_repository(user_id, repo_name, rev, directory_path, format)
     }
 
     pub async fn del_repo_for_user(&self, user_id: &str, repo_name: &str) -> Result<(), AppError> {
@@ -736,8 +1294,122 @@ impl GitService {
         &self,
         user_id: &str,
         repo_name: &str,
-    ) -> Result<Vec<String>, AppError> {
-        self.git_manager.get_repo_branchs(user_id, repo_name).await
+        filter: Option<&str>,
+    ) -> Result<Vec<BranchInfo>, AppError> {
+        self.git_manager.list_branches(user_id, repo_name, filter)
+    }
+
+    pub async fn create_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+        start_point: Option<&str>,
+    ) -> Result<BranchInfo, AppError> {
+        self.git_manager
+            .create_branch(user_id, repo_name, branch_name, start_point)
+    }
+
+    pub async fn delete_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+        force: bool,
+    ) -> Result<(), AppError> {
+        self.git_manager
+            .delete_branch(user_id, repo_name, branch_name, force)
+    }
+
+    pub async fn rename_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+        new_name: &str,
+    ) -> Result<BranchInfo, AppError> {
+        self.git_manager
+            .rename_branch(user_id, repo_name, branch_name, new_name)
+    }
+
+    pub async fn checkout_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        branch_name: &str,
+    ) -> Result<BranchInfo, AppError> {
+        self.git_manager
+            .checkout_branch(user_id, repo_name, branch_name)
+    }
+
+    // 列出标签，并附上数据库中保存的发布说明
+    pub async fn list_tags(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+    ) -> Result<Vec<TagInfo>, AppError> {
+        let mut tags = self.git_manager.list_tags(user_id, repo_name)?;
+        let releases = self.pg_db.list_releases(user_id, repo_name).await?;
+
+        for tag in tags.iter_mut() {
+            tag.release_notes = releases
+                .iter()
+                .find(|release| release.tag_name == tag.name)
+                .map(|release| release.notes.clone());
+        }
+
+        Ok(tags)
+    }
+
+    pub async fn create_tag(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        tag_name: &str,
+        target: Option<&str>,
+        message: Option<&str>,
+        release_notes: Option<&str>,
+    ) -> Result<(), AppError> {
+        // 附注标签使用当前用户作为标签创建者
+        let tagger = self.get_user_identity(user_id).await?;
+        self.git_manager
+            .create_tag(user_id, repo_name, tag_name, target, message, &tagger)?;
+
+        if let Some(notes) = release_notes {
+            self.pg_db
+                .save_release(user_id, repo_name, tag_name, notes)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn delete_tag(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        tag_name: &str,
+    ) -> Result<(), AppError> {
+        self.git_manager.delete_tag(user_id, repo_name, tag_name)?;
+        self.pg_db
+            .delete_release(user_id, repo_name, tag_name)
+            .await
+    }
+
+    pub async fn save_release_notes(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        tag_name: &str,
+        notes: &str,
+    ) -> Result<(), AppError> {
+        if !self.git_manager.tag_exists(user_id, repo_name, tag_name)? {
+            return Err(AppError::NotFound(format!("Tag not found: {}", tag_name)));
+        }
+
+        self.pg_db
+            .save_release(user_id, repo_name, tag_name, notes)
+            .await
     }
 
     pub async fn pull_repo(
@@ -745,7 +1417,108 @@ impl GitService {
         user_id: &str,
         repo_name: &str,
         branch: Option<&str>,
-    ) -> Result<(), AppError> {
-        self.git_manager.pull_repo(user_id, repo_name, branch).await
+        strategy: PullStrategy,
+    ) -> Result<PullResult, AppError> {
+        let credential = self
+            .load_remote_credential(user_id, repo_name, "origin")
+            .await?;
+        // 合并或变基时需要用户身份生成新提交
+        let committer = self.get_user_identity(user_id).await?;
+        self.git_manager
+            .pull_repo(
+                user_id,
+                repo_name,
+                branch,
+                strategy,
+                &committer,
+                credential.as_ref(),
+            )
+            .await
+    }
+
+    pub async fn revert_commit(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        branch: Option<&str>,
+        mainline: Option<u32>,
+        ws_manager: &WebSocketManager,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let committer = self.get_user_identity(user_id).await?;
+        let result = self
+            .git_manager
+            .revert_commit(user_id, repo_name, commit_id, branch, mainline, &committer)?;
+
+        ws_manager
+            .send_repo_event(user_id, repo_name, &format!("COMMITTED:{}", result.head))
+            .await;
+
+        Ok(result)
+    }
+
+    pub async fn cherry_pick_commit(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        branch: Option<&str>,
+        mainline: Option<u32>,
+        ws_manager: &WebSocketManager,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let committer = self.get_user_identity(user_id).await?;
+        let result = self
+            .git_manager
+            .cherry_pick_commit(user_id, repo_name, commit_id, branch, mainline, &committer)?;
+
+        ws_manager
+            .send_repo_event(user_id, repo_name, &format!("COMMITTED:{}", result.head))
+            .await;
+
+        Ok(result)
+    }
+
+    pub async fn reset_branch(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        commit_id: &str,
+        branch: Option<&str>,
+        mode: ResetMode,
+        ws_manager: &WebSocketManager,
+    ) -> Result<HistoryOperationResult, AppError> {
+        let result = self
+            .git_manager
+            .reset_branch(user_id, repo_name, commit_id, branch, mode)?;
+
+        // 分支被移到已有的提交上，与新提交区分开
+        ws_manager
+            .send_repo_event(user_id, repo_name, &format!("RESET:{}", result.head))
+            .await;
+
+        Ok(result)
+    }
+
+    pub async fn push_repo(
+        &self,
+        user_id: &str,
+        repo_name: &str,
+        remote_name: Option<&str>,
+        branch: Option<&str>,
+        ws_manager: &WebSocketManager,
+    ) -> Result<PushResult, AppError> {
+        let credential = self
+            .load_remote_credential(user_id, repo_name, remote_name.unwrap_or("origin"))
+            .await?;
+        self.git_manager
+            .push_repo(
+                user_id,
+                repo_name,
+                remote_name,
+                branch,
+                credential,
+                ws_manager,
+            )
+            .await
     }
 }
@@ -0,0 +1,49 @@
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use sha2::{Digest, Sha256};
+
+use super::{error::AppError, setting};
+
+const NONCE_LEN: usize = 12;
+
+// 由配置中的密钥派生出 AES-256 密钥
+fn cipher() -> Aes256Gcm {
+    let setting = setting::get_config();
+    let digest = Sha256::digest(&setting.credential.encryption_key);
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&digest))
+}
+
+/// 加密敏感数据，返回 base64(nonce || ciphertext)
+pub fn encrypt_secret(plain: &str) -> Result<String, AppError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher()
+        .encrypt(&nonce, plain.as_bytes())
+        .map_err(|_| AppError::InternalServerError("Failed to encrypt secret".to_string()))?;
+
+    let mut data = nonce.to_vec();
+    data.extend_from_slice(&ciphertext);
+    Ok(STANDARD.encode(data))
+}
+
+/// 解密由 encrypt_secret 生成的数据
+pub fn decrypt_secret(encoded: &str) -> Result<String, AppError> {
+    let data = STANDARD
+        .decode(encoded)
+        .map_err(|_| AppError::InternalServerError("Invalid encrypted secret".to_string()))?;
+    if data.len() < NONCE_LEN {
+        return Err(AppError::InternalServerError(
+            "Invalid encrypted secret".to_string(),
+        ));
+    }
+
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let plain = cipher()
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::InternalServerError("Failed to decrypt secret".to_string()))?;
+
+    String::from_utf8(plain)
+        .map_err(|_| AppError::InternalServerError("Decrypted secret is not UTF-8".to_string()))
+}
@@ -1,4 +1,4 @@
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{Json, http::StatusCode, response::IntoResponse};
 use validator::ValidationErrors;
 
 #[derive(Debug)]
@@ -8,6 +8,8 @@ pub enum AppError {
     InternalServerError(String),
     BadRequest(String),
     NotFound(String),
+    // 带结构化数据的冲突错误，例如 push 被拒绝、合并冲突
+    Conflict(String, serde_json::Value),
 }
 
 impl IntoResponse for AppError {
@@ -24,6 +26,15 @@ impl IntoResponse for AppError {
                 (StatusCode::BAD_REQUEST, msg)
             }
             Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            Self::Conflict(msg, data) => {
+                // 与 ApiResponse 保持相同的 JSON 结构，方便前端解析
+                let body = Json(serde_json::json!({
+                    "code": StatusCode::CONFLICT.as_u16(),
+                    "message": msg,
+                    "data": data,
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
         }
         .into_response()
     }
@@ -1,4 +1,5 @@
 pub mod auth_middleware;
+pub mod crypto;
 pub mod error;
 pub mod jwt;
 pub mod response;
@@ -6,6 +6,11 @@ use std::sync::RwLock;
 pub struct Settings {
     pub jwt: JwtConfig,
     pub git_path: GitPathConfig,
+    pub credential: CredentialConfig,
+    #[serde(default)]
+    pub diff: DiffConfig,
+    #[serde(default)]
+    pub upload: UploadConfig,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,6 +25,49 @@ pub struct GitPathConfig {
     pub repositories_path: Vec<u8>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CredentialConfig {
+    // 用于加密用户远程仓库凭据的密钥
+    #[serde(deserialize_with = "deserialize")]
+    pub encryption_key: Vec<u8>,
+}
+
+// 单次差异响应的上限，超出的文件标记为 truncated，需要单独加载
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct DiffConfig {
+    pub max_files: usize,          // 最多生成补丁的文件数
+    pub max_lines_per_file: usize, // 单个文件补丁的最大行数
+    pub max_bytes_per_file: usize, // 单个文件补丁的最大字节数
+}
+
+impl Default for DiffConfig {
+    fn default() -> Self {
+        DiffConfig {
+            max_files: 300,
+            max_lines_per_file: 5000,
+            max_bytes_per_file: 512 * 1024,
+        }
+    }
+}
+
+// 上传文件的大小限制，超出时中止上传，不会产生提交
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct UploadConfig {
+    pub max_file_size: u64,  // 单个文件的最大字节数
+    pub max_total_size: u64, // 一次上传所有文件的最大字节数
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        UploadConfig {
+            max_file_size: 20 * 1024 * 1024,
+            max_total_size: 100 * 1024 * 1024,
+        }
+    }
+}
+
 fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
 where
     D: serde::Deserializer<'de>,
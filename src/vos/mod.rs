@@ -1,6 +1,7 @@
 use chrono::DateTime;
 use serde::Serialize;
 
+use crate::models::credential::CredentialKind;
 use crate::models::message::{Message, MessageStatus, MessageType};
 pub mod userdata;
 
@@ -19,3 +20,12 @@ pub struct UserMsg {
     pub read_status: MessageStatus,
     pub created_at: DateTime<chrono::Utc>,
 }
+
+// 凭据只返回元数据，不包含 secret
+#[derive(Debug, Serialize)]
+pub struct CredentialVo {
+    pub host: String,
+    pub kind: CredentialKind,
+    pub auth_username: Option<String>,
+    pub created_at: DateTime<chrono::Utc>,
+}